@@ -0,0 +1,102 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sudoku_solver::{parse_oneline, Encoding, Sudoku, SudokuTemplate};
+
+/// The example from the README, solved by singles alone.
+const EASY: &str =
+    "6....4..1..1....495...1....157....96..4.96..33...45.18....7....76..2......85..3.4";
+/// Needs naked pairs, but no search.
+const MEDIUM: &str =
+    ".7.142..8......1.....6...97..4...7........4.5..6....2..8.37.21.32..84......2....4";
+/// AI Escargot, one of the hardest known puzzles for people.
+const ESCARGOT: &str =
+    "1....7.9..3..2...8..96..5....53..9...1..8...26....4...3......1..4......7..7...3..";
+/// Solutions of the empty grid counted per iteration.
+const EMPTY_SOLUTIONS: usize = 100;
+/// Hard puzzles with their solutions, one per line separated by a tab.
+const HARD: &str = include_str!("../tests/data/hard.tsv");
+
+fn bench_encoding(c: &mut Criterion, name: &str, encoding: Encoding) {
+    let easy = parse_oneline(EASY).expect("valid puzzle");
+    let escargot = parse_oneline(ESCARGOT).expect("valid puzzle");
+    let empty = [[0u32; 9]; 9];
+
+    let mut group = c.benchmark_group(name);
+    group.bench_function("easy", |b| {
+        b.iter(|| {
+            let mut sudoku = Sudoku::new_with_encoding(black_box(easy), encoding);
+            sudoku.solve().expect("solvable")
+        })
+    });
+    group.bench_function("escargot", |b| {
+        b.iter(|| {
+            let mut sudoku = Sudoku::new_with_encoding(black_box(escargot), encoding);
+            sudoku.solve().expect("solvable")
+        })
+    });
+    group.bench_function("empty_count", |b| {
+        b.iter(|| {
+            let mut sudoku = Sudoku::new_with_encoding(black_box(empty), encoding);
+            sudoku.count_solutions(EMPTY_SOLUTIONS)
+        })
+    });
+    group.finish();
+}
+
+fn pairwise(c: &mut Criterion) {
+    bench_encoding(c, "pairwise", Encoding::Pairwise);
+}
+
+fn sequential(c: &mut Criterion) {
+    bench_encoding(c, "sequential", Encoding::Sequential);
+}
+
+/// Logic first with a SAT fallback against plain SAT, both with the
+/// default encoding.
+fn hybrid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hybrid");
+    for (name, puzzle) in [("easy", EASY), ("medium", MEDIUM), ("escargot", ESCARGOT)] {
+        let grid = parse_oneline(puzzle).expect("valid puzzle");
+        group.bench_function(format!("{}_sat", name), |b| {
+            b.iter(|| Sudoku::new(black_box(grid)).solve().expect("solvable"))
+        });
+        group.bench_function(format!("{}_hybrid", name), |b| {
+            b.iter(|| {
+                Sudoku::new(black_box(grid))
+                    .solve_hybrid()
+                    .expect("solvable")
+            })
+        });
+    }
+    group.finish();
+}
+
+/// A new encoding per puzzle against one template for all of them, like
+/// `--batch` mode, over the puzzles of the test dataset.
+fn template(c: &mut Criterion) {
+    let puzzles = HARD
+        .lines()
+        .filter_map(|line| line.split('\t').next())
+        .map(|puzzle| parse_oneline(puzzle).expect("valid puzzle"))
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("template");
+    group.bench_function("hard_fresh", |b| {
+        b.iter(|| {
+            for &puzzle in &puzzles {
+                Sudoku::new(black_box(puzzle)).solve().expect("solvable");
+            }
+        })
+    });
+    group.bench_function("hard_template", |b| {
+        b.iter(|| {
+            let mut template = SudokuTemplate::new();
+            for &puzzle in &puzzles {
+                template.solve(black_box(puzzle)).expect("solvable");
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, pairwise, sequential, hybrid, template);
+criterion_main!(benches);
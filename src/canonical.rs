@@ -0,0 +1,117 @@
+use crate::Sudoku;
+
+/// Every permutation of three elements.
+const PERMUTATIONS: [[usize; 3]; 6] = [
+    [0, 1, 2],
+    [0, 2, 1],
+    [1, 0, 2],
+    [1, 2, 0],
+    [2, 0, 1],
+    [2, 1, 0],
+];
+
+impl Sudoku {
+    /// Returns the canonical form of the puzzle as given: the
+    /// lexicographically smallest grid, read row by row with `0` for empty
+    /// cells, among all grids equivalent to it.
+    ///
+    /// Two grids are equivalent if one turns into the other by relabelling
+    /// the values, reordering the bands, the rows within a band, the stacks
+    /// and the columns within a stack, and transposing. Rotations and
+    /// reflections are combinations of these. Equivalent puzzles have the
+    /// same canonical form, which makes it a key for finding duplicates.
+    ///
+    /// All `2 * 6^8` cell permutations are tried, for each the smallest
+    /// relabelling is numbering the values in order of first appearance. A
+    /// permutation is dropped as soon as its grid compares greater than
+    /// the best one so far, which is usually within the first row.
+    pub fn canonical(&self) -> [[u32; 9]; 9] {
+        let mut transposed = [[0u32; 9]; 9];
+        for (i, row) in self.givens.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                transposed[j][i] = value;
+            }
+        }
+
+        let orders = line_orders();
+        let mut best = [[u32::MAX; 9]; 9];
+        let mut candidate = [[0u32; 9]; 9];
+        for grid in [&self.givens, &transposed] {
+            for rows in &orders {
+                for cols in &orders {
+                    if relabel(grid, rows, cols, &best, &mut candidate) {
+                        best = candidate;
+                    }
+                }
+            }
+        }
+        best
+    }
+
+    /// Returns true if both puzzles have the same canonical form, i.e. one
+    /// is the other with its values relabelled and its rows and columns
+    /// reordered as [`Sudoku::canonical`] allows, such as a rotation.
+    /// Only the givens are compared.
+    pub fn is_equivalent(&self, other: &Sudoku) -> bool {
+        self.canonical() == other.canonical()
+    }
+}
+
+/// Every order of the nine rows (or columns) that keeps the bands (or
+/// stacks) together.
+fn line_orders() -> Vec<[usize; 9]> {
+    let mut orders = vec![];
+    for bands in &PERMUTATIONS {
+        for first in &PERMUTATIONS {
+            for second in &PERMUTATIONS {
+                for third in &PERMUTATIONS {
+                    let mut order = [0; 9];
+                    for (b, within) in [first, second, third].iter().enumerate() {
+                        for (l, &line) in within.iter().enumerate() {
+                            order[3 * b + l] = 3 * bands[b] + line;
+                        }
+                    }
+                    orders.push(order);
+                }
+            }
+        }
+    }
+    orders
+}
+
+/// Writes `grid` with its rows and columns reordered and its values
+/// numbered in order of first appearance to `out`. Returns whether that is
+/// smaller than `best`, giving up as soon as it can't be.
+fn relabel(
+    grid: &[[u32; 9]; 9],
+    rows: &[usize; 9],
+    cols: &[usize; 9],
+    best: &[[u32; 9]; 9],
+    out: &mut [[u32; 9]; 9],
+) -> bool {
+    let mut labels = [0u32; 10];
+    let mut next = 1;
+    let mut smaller = false;
+    for (i, &r) in rows.iter().enumerate() {
+        for (j, &c) in cols.iter().enumerate() {
+            let value = grid[r][c];
+            let label = if value == 0 {
+                0
+            } else {
+                if labels[value as usize] == 0 {
+                    labels[value as usize] = next;
+                    next += 1;
+                }
+                labels[value as usize]
+            };
+            if !smaller {
+                if label > best[i][j] {
+                    return false;
+                }
+                smaller = label < best[i][j];
+            }
+            out[i][j] = label;
+        }
+    }
+    smaller
+}
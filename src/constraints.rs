@@ -0,0 +1,523 @@
+use crate::encoding::Encoder;
+use crate::{BoxShape, Encoding, Sudoku};
+use rsat::Lit;
+
+/// Extra rules of Sudoku variants, all of them are off by default.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Constraints {
+    /// X-Sudoku: both main diagonals contain every value exactly once.
+    pub diagonals: bool,
+    /// Windoku: the windows, extra boxes one cell away from the border and
+    /// from each other, contain every value exactly once.
+    pub windoku: bool,
+    /// Anti-knight: cells a chess knight's move apart never share a value.
+    pub anti_knight: bool,
+    /// Anti-king: cells a chess king's move apart never share a value.
+    pub anti_king: bool,
+    /// Killer Sudoku cages.
+    pub cages: Vec<Cage>,
+    /// Futoshiki inequalities between cells.
+    pub inequalities: Vec<Inequality>,
+    /// Thermo-Sudoku thermometers.
+    pub thermos: Vec<Thermo>,
+    /// Cells that hold an even value.
+    pub evens: Vec<(usize, usize)>,
+    /// Cells that hold an odd value.
+    pub odds: Vec<(usize, usize)>,
+    /// Sandwich clue of each row, the sum of the values between the `1`
+    /// and the `N` of the row. Rows past the end of the list have no clue.
+    pub row_sandwiches: Vec<Option<u32>>,
+    /// Sandwich clue of each column, like `row_sandwiches`.
+    pub col_sandwiches: Vec<Option<u32>>,
+    /// Pairs of orthogonally adjacent cells whose values differ by one.
+    pub consecutive: Vec<((usize, usize), (usize, usize))>,
+    /// Orthogonally adjacent cells not in `consecutive` never differ by one.
+    pub nonconsecutive_others: bool,
+}
+
+/// A Killer Sudoku cage: the values of `cells` are all different and add up
+/// to `sum`.
+///
+/// The sum is encoded by forbidding every assignment of distinct values
+/// with a different total, which takes up to `N!/(N-s)!` clauses for a cage
+/// of `s` cells, so cages are limited to [`Cage::MAX_CELLS`] cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cage {
+    pub cells: Vec<(usize, usize)>,
+    pub sum: u32,
+}
+
+impl Cage {
+    /// Largest supported cage, a 9x9 board needs 3024 clauses for the sum of
+    /// a four cell cage.
+    pub const MAX_CELLS: usize = 4;
+}
+
+/// A Futoshiki inequality: the value of `a` is less than the value of `b`
+/// if `a_less_than_b`, greater otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inequality {
+    pub a: (usize, usize),
+    pub b: (usize, usize),
+    pub a_less_than_b: bool,
+}
+
+/// A thermometer: the values strictly increase along `cells`, which start
+/// at the bulb.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Thermo {
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl<const N: usize> Sudoku<N> {
+    /// Builds the SAT encoding of an X-Sudoku, where both main diagonals
+    /// contain every value exactly once.
+    pub fn with_diagonals(grid: [[u32; N]; N]) -> Self {
+        let constraints = Constraints {
+            diagonals: true,
+            ..Constraints::default()
+        };
+        Self::with_constraints(grid, &constraints)
+    }
+
+    /// Builds the SAT encoding of a Windoku, where the four windows at rows
+    /// and columns 2-4 and 6-8 contain every value exactly once. Other board
+    /// sizes get a window at every box-sized square one cell away from the
+    /// border and from each other.
+    pub fn with_windoku(grid: [[u32; N]; N]) -> Self {
+        let constraints = Constraints {
+            windoku: true,
+            ..Constraints::default()
+        };
+        Self::with_constraints(grid, &constraints)
+    }
+
+    /// Builds the SAT encoding of a Killer Sudoku.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a cage has more than [`Cage::MAX_CELLS`] cells.
+    pub fn with_cages(grid: [[u32; N]; N], cages: Vec<Cage>) -> Self {
+        let constraints = Constraints {
+            cages,
+            ..Constraints::default()
+        };
+        Self::with_constraints(grid, &constraints)
+    }
+
+    /// Builds the SAT encoding of a Futoshiki: every row and column contains
+    /// each value once, there are no boxes, and the inequalities hold.
+    pub fn futoshiki(grid: [[u32; N]; N], inequalities: Vec<Inequality>) -> Self {
+        let constraints = Constraints {
+            inequalities,
+            ..Constraints::default()
+        };
+        // One box per row adds nothing to the row rules
+        let shape = BoxShape { rows: 1, cols: N };
+        Self::with_options(grid, &constraints, Encoding::default(), shape)
+    }
+
+    /// Builds the SAT encoding of a Thermo-Sudoku.
+    pub fn with_thermos(grid: [[u32; N]; N], thermos: Vec<Thermo>) -> Self {
+        let constraints = Constraints {
+            thermos,
+            ..Constraints::default()
+        };
+        Self::with_constraints(grid, &constraints)
+    }
+
+    /// Builds the SAT encoding of a Sudoku where the cells in `evens` hold
+    /// even values and the cells in `odds` odd ones.
+    pub fn with_parity(
+        grid: [[u32; N]; N],
+        evens: Vec<(usize, usize)>,
+        odds: Vec<(usize, usize)>,
+    ) -> Self {
+        let constraints = Constraints {
+            evens,
+            odds,
+            ..Constraints::default()
+        };
+        Self::with_constraints(grid, &constraints)
+    }
+
+    /// Builds the SAT encoding of a Sandwich Sudoku: in every row and
+    /// column with a clue, the values between the `1` and the `N` add up
+    /// to it. `None` marks a line without a clue.
+    pub fn with_sandwiches(
+        grid: [[u32; N]; N],
+        row_sums: [Option<u32>; N],
+        col_sums: [Option<u32>; N],
+    ) -> Self {
+        let constraints = Constraints {
+            row_sandwiches: row_sums.to_vec(),
+            col_sandwiches: col_sums.to_vec(),
+            ..Constraints::default()
+        };
+        Self::with_constraints(grid, &constraints)
+    }
+
+    /// Builds the SAT encoding of a Sudoku where the cells of each pair in
+    /// `marks` hold consecutive values, and if `all_others_nonconsecutive`
+    /// no other orthogonally adjacent cells do.
+    pub fn with_consecutive(
+        grid: [[u32; N]; N],
+        marks: Vec<((usize, usize), (usize, usize))>,
+        all_others_nonconsecutive: bool,
+    ) -> Self {
+        let constraints = Constraints {
+            consecutive: marks,
+            nonconsecutive_others: all_others_nonconsecutive,
+            ..Constraints::default()
+        };
+        Self::with_constraints(grid, &constraints)
+    }
+
+    /// Cells of each Windoku window in reading order.
+    fn windows(&self) -> Vec<Vec<(usize, usize)>> {
+        let shape = self.box_shape.expect("Windoku windows need boxes");
+        // A gap of one cell before each window, which has to end before
+        // the last row or column
+        let starts = |side: usize| (1..).step_by(side + 1).take_while(move |&s| s + side < N);
+        let mut windows = vec![];
+        for top in starts(shape.rows) {
+            for left in starts(shape.cols) {
+                windows.push(
+                    (0..N)
+                        .map(|c| (top + c / shape.cols, left + c % shape.cols))
+                        .collect(),
+                );
+            }
+        }
+        windows
+    }
+
+    pub(crate) fn add_constraints(&mut self, lits: &[[[Lit; N]; N]; N], constraints: &Constraints) {
+        if constraints.diagonals {
+            let main = (0..N).map(|i| (i, i)).collect::<Vec<_>>();
+            let anti = (0..N).map(|i| (i, N - 1 - i)).collect::<Vec<_>>();
+            self.add_all_different(lits, &main);
+            self.add_all_different(lits, &anti);
+        }
+        if constraints.windoku {
+            for window in self.windows() {
+                self.add_all_different(lits, &window);
+            }
+        }
+        if constraints.anti_knight {
+            self.add_apart_different(lits, &[(1, -2), (1, 2), (2, -1), (2, 1)]);
+        }
+        if constraints.anti_king {
+            // Orthogonal neighbours already share a row or a column
+            self.add_apart_different(lits, &[(1, -1), (1, 1)]);
+        }
+        for cage in &constraints.cages {
+            self.add_cage(lits, cage);
+        }
+        for inequality in &constraints.inequalities {
+            self.add_inequality(lits, inequality);
+        }
+        // Value k+1 is odd for even k
+        for &(i, j) in &constraints.evens {
+            for k in (0..N).step_by(2) {
+                self.add_clause(vec![!lits[i][j][k]]);
+            }
+        }
+        for &(i, j) in &constraints.odds {
+            for k in (1..N).step_by(2) {
+                self.add_clause(vec![!lits[i][j][k]]);
+            }
+        }
+        for (i, &sum) in constraints.row_sandwiches.iter().enumerate() {
+            if let Some(sum) = sum {
+                let row = (0..N).map(|j| (i, j)).collect::<Vec<_>>();
+                self.add_sandwich(lits, &row, sum);
+            }
+        }
+        for (j, &sum) in constraints.col_sandwiches.iter().enumerate() {
+            if let Some(sum) = sum {
+                let col = (0..N).map(|i| (i, j)).collect::<Vec<_>>();
+                self.add_sandwich(lits, &col, sum);
+            }
+        }
+        for &(a, b) in &constraints.consecutive {
+            self.add_pair_rule(lits, a, b, |ka, kb| ka.abs_diff(kb) == 1);
+        }
+        if constraints.nonconsecutive_others {
+            for i in 0..N {
+                for j in 0..N {
+                    for (i2, j2) in [(i, j + 1), (i + 1, j)] {
+                        if i2 == N || j2 == N {
+                            continue;
+                        }
+                        let marked = constraints.consecutive.iter().any(|&(a, b)| {
+                            (a, b) == ((i, j), (i2, j2)) || (b, a) == ((i, j), (i2, j2))
+                        });
+                        if !marked {
+                            self.add_pair_rule(lits, (i, j), (i2, j2), |ka, kb| {
+                                ka.abs_diff(kb) != 1
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        for thermo in &constraints.thermos {
+            for step in thermo.cells.windows(2) {
+                let inequality = Inequality {
+                    a: step[0],
+                    b: step[1],
+                    a_less_than_b: true,
+                };
+                self.add_inequality(lits, &inequality);
+            }
+        }
+    }
+
+    fn add_inequality(&mut self, lits: &[[[Lit; N]; N]; N], inequality: &Inequality) {
+        let a_less_than_b = inequality.a_less_than_b;
+        self.add_pair_rule(lits, inequality.a, inequality.b, |ka, kb| {
+            ka != kb && (ka < kb) == a_less_than_b
+        });
+    }
+
+    /// Forbids every pair of values of cells `a` and `b` for which
+    /// `allowed(ka, kb)` is false, `ka` and `kb` being the values minus one.
+    fn add_pair_rule(
+        &mut self,
+        lits: &[[[Lit; N]; N]; N],
+        (ia, ja): (usize, usize),
+        (ib, jb): (usize, usize),
+        allowed: impl Fn(usize, usize) -> bool,
+    ) {
+        for (ka, &a) in lits[ia][ja].iter().enumerate() {
+            for (kb, &b) in lits[ib][jb].iter().enumerate() {
+                if !allowed(ka, kb) {
+                    self.add_clause(vec![!a, !b]);
+                }
+            }
+        }
+    }
+
+    fn add_cage(&mut self, lits: &[[[Lit; N]; N]; N], cage: &Cage) {
+        assert!(
+            cage.cells.len() <= Cage::MAX_CELLS,
+            "cages with more than {} cells aren't supported",
+            Cage::MAX_CELLS
+        );
+        self.add_all_different(lits, &cage.cells);
+
+        let mut tuples = vec![];
+        distinct_tuples(N as u32, cage.cells.len(), &mut vec![], &mut tuples);
+        let mut possible = vec![[false; N]; cage.cells.len()];
+        for values in tuples {
+            if values.iter().sum::<u32>() == cage.sum {
+                for (p, &v) in values.iter().enumerate() {
+                    possible[p][v as usize - 1] = true;
+                }
+            } else {
+                // The cage cells can't take these values all at once
+                let cl = cage
+                    .cells
+                    .iter()
+                    .zip(&values)
+                    .map(|(&(i, j), &v)| !lits[i][j][v as usize - 1])
+                    .collect();
+                self.add_clause(cl);
+            }
+        }
+
+        // Rule out values that appear in no combination with the right sum,
+        // implied by the clauses above but much easier for the solver
+        for (&(i, j), possible) in cage.cells.iter().zip(&possible) {
+            for (k, &possible) in possible.iter().enumerate() {
+                if !possible {
+                    self.add_clause(vec![!lits[i][j][k]]);
+                }
+            }
+        }
+    }
+
+    /// The values strictly between the `1` and the `N` of `line` add up to
+    /// `sum`.
+    ///
+    /// `ends[p]` is true if cell `p` holds `1` or `N`, and a line has one of
+    /// each. The sum is counted by [`Sudoku::add_sandwich_sum`] once from
+    /// each end of the line: both count the same cells, but the solver
+    /// propagates a partial sum only in the direction it was built, and
+    /// going both ways makes a full grid of clues solve in about a second
+    /// instead of several. Auxiliary variables come after the cell
+    /// variables.
+    fn add_sandwich(&mut self, lits: &[[[Lit; N]; N]; N], line: &[(usize, usize)], sum: u32) {
+        let sum = sum as usize;
+        let ends = line
+            .iter()
+            .map(|&(i, j)| {
+                let end = self.new_lit();
+                let (low, high) = (lits[i][j][0], lits[i][j][N - 1]);
+                self.add_clause(vec![!low, end]);
+                self.add_clause(vec![!high, end]);
+                self.add_clause(vec![!end, low, high]);
+                end
+            })
+            .collect::<Vec<_>>();
+
+        // m different values from 2..N add up to between 2 + ... + (m + 1)
+        // and (N - m) + ... + (N - 1), ends too close or too far apart for
+        // `sum` rule each other out. Implied by the sum, but only after the
+        // solver placed the cells between them.
+        for p in 0..line.len() {
+            for q in p + 1..line.len() {
+                let m = q - p - 1;
+                let least = (2..m + 2).sum::<usize>();
+                let most = (N - m..N).sum::<usize>();
+                if sum < least || sum > most {
+                    self.add_clause(vec![!ends[p], !ends[q]]);
+                }
+            }
+        }
+
+        self.add_sandwich_sum(lits, line, &ends, sum);
+        let (mut line, mut ends) = (line.to_vec(), ends);
+        line.reverse();
+        ends.reverse();
+        self.add_sandwich_sum(lits, &line, &ends, sum);
+    }
+
+    /// The values between the two true `ends` of `line` add up to `sum`,
+    /// counting from the start of the line.
+    ///
+    /// `seen` is true once an end is at or before the current cell and
+    /// `both` once both are, so a cell is in the sandwich if an end came
+    /// before it and it isn't the second one. The sum is an order encoding
+    /// of the prefixes of the line: `at_least[s - 1]` means the cells of
+    /// the sandwich so far add up to `s` or more, with a last level for
+    /// more than `sum` that is never reached. A cell in the sandwich moves
+    /// the prefix up by its value, the others keep it. That takes
+    /// `O(N * N * sum)` clauses, and an unfinished sandwich already bounds
+    /// the values left for it.
+    fn add_sandwich_sum(
+        &mut self,
+        lits: &[[[Lit; N]; N]; N],
+        line: &[(usize, usize)],
+        ends: &[Lit],
+        sum: usize,
+    ) {
+        // Before the first cell no end is seen and the prefix sum is 0
+        let mut seen = self.new_lit();
+        let mut both = self.new_lit();
+        self.add_clause(vec![!seen]);
+        self.add_clause(vec![!both]);
+        // at_least[s - 1] for the sums 1..=sum + 1 of the prefix so far
+        let mut at_least = (0..=sum).map(|_| self.new_lit()).collect::<Vec<_>>();
+        for &lit in &at_least {
+            self.add_clause(vec![!lit]);
+        }
+
+        for (&(i, j), &end) in line.iter().zip(ends) {
+            let (next_seen, next_both, inside) = (self.new_lit(), self.new_lit(), self.new_lit());
+            // next_seen == seen || end
+            self.add_clause(vec![!seen, next_seen]);
+            self.add_clause(vec![!end, next_seen]);
+            self.add_clause(vec![!next_seen, seen, end]);
+            // next_both == both || (seen && end)
+            self.add_clause(vec![!both, next_both]);
+            self.add_clause(vec![!seen, !end, next_both]);
+            self.add_clause(vec![!next_both, both, seen]);
+            self.add_clause(vec![!next_both, both, end]);
+            // inside == seen && !next_both
+            self.add_clause(vec![!inside, seen]);
+            self.add_clause(vec![!inside, !next_both]);
+            self.add_clause(vec![!seen, next_both, inside]);
+
+            let next = (0..=sum).map(|_| self.new_lit()).collect::<Vec<_>>();
+            // The prefix more than `sum` is never reached
+            self.add_clause(vec![!next[sum]]);
+            for s in 1..=sum + 1 {
+                if s > 1 {
+                    self.add_clause(vec![!next[s - 1], next[s - 2]]);
+                }
+                // Cells outside the sandwich keep the prefix as it was
+                self.add_clause(vec![!at_least[s - 1], next[s - 1]]);
+                self.add_clause(vec![inside, at_least[s - 1], !next[s - 1]]);
+                for (k, &value) in lits[i][j].iter().enumerate() {
+                    let v = k + 1;
+                    // A prefix below s - v stays below s
+                    if s > v {
+                        self.add_clause(vec![!value, at_least[s - v - 1], !next[s - 1]]);
+                    }
+                    // A prefix of at least s reaches s + v inside
+                    let reached = (s + v).min(sum + 1);
+                    self.add_clause(vec![!inside, !value, !at_least[s - 1], next[reached - 1]]);
+                }
+            }
+            // From a prefix of 0, the value alone gives the new prefix
+            for (k, &value) in lits[i][j].iter().enumerate() {
+                let reached = (k + 1).min(sum + 1);
+                self.add_clause(vec![!inside, !value, next[reached - 1]]);
+            }
+            seen = next_seen;
+            both = next_both;
+            at_least = next;
+        }
+        // The whole sandwich adds up to `sum` exactly, more was ruled out
+        if sum > 0 {
+            self.add_clause(vec![at_least[sum - 1]]);
+        }
+    }
+
+    /// No two cells `offset` apart are assigned the same value.
+    ///
+    /// Only offsets pointing forward in reading order are passed, the
+    /// opposite ones would constrain the same pairs a second time.
+    fn add_apart_different(&mut self, lits: &[[[Lit; N]; N]; N], offsets: &[(usize, isize)]) {
+        for i in 0..N {
+            for j in 0..N {
+                for &(di, dj) in offsets {
+                    let (i2, j2) = (i + di, j as isize + dj);
+                    if i2 >= N || j2 < 0 || j2 >= N as isize {
+                        continue;
+                    }
+                    let j2 = j2 as usize;
+                    for (&a, &b) in lits[i][j].iter().zip(&lits[i2][j2]) {
+                        // Cell(i, j) == k+1 => Cell(i2, j2) != k+1
+                        self.add_clause(vec![!a, !b]);
+                    }
+                }
+            }
+        }
+    }
+
+    /// No two of `cells` are assigned the same value.
+    pub(crate) fn add_all_different(
+        &mut self,
+        lits: &[[[Lit; N]; N]; N],
+        cells: &[(usize, usize)],
+    ) {
+        let cell_lits = cells
+            .iter()
+            .map(|&(i, j)| &lits[i][j][..])
+            .collect::<Vec<_>>();
+        self.add_distinct_values(&cell_lits);
+    }
+}
+
+/// Collects every sequence of `len` distinct values in `1..=max`.
+fn distinct_tuples(max: u32, len: usize, prefix: &mut Vec<u32>, out: &mut Vec<Vec<u32>>) {
+    if prefix.len() == len {
+        out.push(prefix.clone());
+        return;
+    }
+    for v in 1..=max {
+        if !prefix.contains(&v) {
+            prefix.push(v);
+            distinct_tuples(max, len, prefix, out);
+            prefix.pop();
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use crate::Sudoku;
+use rsat::Lit;
+
+/// How "at most one of these literals is true" is turned into clauses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// A binary clause for every pair of literals: `n * (n - 1) / 2` clauses
+    /// and no extra variables.
+    #[default]
+    Pairwise,
+    /// Sinz's sequential counter: `3n - 4` clauses and `n - 1` auxiliary
+    /// variables, auxiliary variables come after the cell variables.
+    Sequential,
+}
+
+/// A puzzle whose rules are being turned into clauses, i.e. a [`Sudoku`] or
+/// a [`crate::Samurai`], so both encode them the same way.
+pub(crate) trait Encoder {
+    /// Literal of a new auxiliary variable.
+    fn new_lit(&mut self) -> Lit;
+
+    /// Adds a clause to the solver, remembering if it was already falsified.
+    fn add_clause(&mut self, clause: Vec<Lit>);
+
+    /// How "at most one" rules are encoded.
+    fn encoding(&self) -> Encoding;
+
+    /// At most one of `lits` is true.
+    fn add_at_most_one(&mut self, lits: &[Lit]) {
+        let n = lits.len();
+        // Below five literals pairwise needs no more clauses than the counter
+        if self.encoding() == Encoding::Pairwise || n <= 4 {
+            for (p, &a) in lits.iter().enumerate() {
+                for &b in &lits[p + 1..] {
+                    self.add_clause(vec![!a, !b]);
+                }
+            }
+            return;
+        }
+
+        // s[p] is true if one of lits[0..=p] is true
+        let s = (0..n - 1).map(|_| self.new_lit()).collect::<Vec<_>>();
+        self.add_clause(vec![!lits[0], s[0]]);
+        for p in 1..n - 1 {
+            self.add_clause(vec![!lits[p], s[p]]);
+            self.add_clause(vec![!s[p - 1], s[p]]);
+            self.add_clause(vec![!lits[p], !s[p - 1]]);
+        }
+        self.add_clause(vec![!lits[n - 1], !s[n - 2]]);
+    }
+
+    /// Exactly one value is assigned to a cell, `cell[k]` being true if it
+    /// is assigned k+1.
+    fn add_one_value(&mut self, cell: &[Lit]) {
+        self.add_clause(cell.to_vec());
+        self.add_at_most_one(cell);
+    }
+
+    /// No two of `cells` are assigned the same value.
+    fn add_distinct_values(&mut self, cells: &[&[Lit]]) {
+        let n = cells.first().map_or(0, |cell| cell.len());
+        for k in 0..n {
+            // Cell(i, j) == k+1 => Cell(i2, j2) != k+1
+            let group = cells.iter().map(|cell| cell[k]).collect::<Vec<_>>();
+            self.add_at_most_one(&group);
+        }
+    }
+}
+
+impl<const N: usize> Encoder for Sudoku<N> {
+    fn new_lit(&mut self) -> Lit {
+        Lit::new(self.solver.new_var(), false)
+    }
+
+    fn add_clause(&mut self, clause: Vec<Lit>) {
+        self.clauses.push(clause.clone());
+        if !self.solver.new_clause(clause) {
+            self.contradiction = true;
+        }
+    }
+
+    fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+}
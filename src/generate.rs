@@ -0,0 +1,239 @@
+use crate::{BoxShape, Sudoku};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// Symmetry of the clue positions of a puzzle, see
+/// [`Sudoku::generate_with_symmetry`] and [`Sudoku::clue_symmetry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symmetry {
+    /// Clues can be anywhere.
+    #[default]
+    None,
+    /// Turning the grid by 180 degrees keeps the clue positions.
+    Rotational180,
+    /// Mirroring the grid top to bottom keeps the clue positions.
+    Horizontal,
+    /// Mirroring the grid left to right keeps the clue positions.
+    Vertical,
+    /// Turning the grid by 90 degrees keeps the clue positions, and so do
+    /// turns by 180 and 270 degrees.
+    Rotational90,
+    /// Mirroring the grid along the diagonal from the top left corner
+    /// keeps the clue positions.
+    Diagonal,
+    /// Mirroring the grid along the diagonal from the top right corner
+    /// keeps the clue positions.
+    AntiDiagonal,
+}
+
+impl Symmetry {
+    /// Every symmetry but [`Symmetry::None`], which any puzzle has.
+    const ALL: [Symmetry; 6] = [
+        Symmetry::Rotational180,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Rotational90,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ];
+
+    /// Cell that `(i, j)` is moved to on a board of side `n`.
+    fn image(self, n: usize, i: usize, j: usize) -> (usize, usize) {
+        match self {
+            Symmetry::None => (i, j),
+            Symmetry::Rotational180 => (n - 1 - i, n - 1 - j),
+            Symmetry::Horizontal => (n - 1 - i, j),
+            Symmetry::Vertical => (i, n - 1 - j),
+            Symmetry::Rotational90 => (j, n - 1 - i),
+            Symmetry::Diagonal => (j, i),
+            Symmetry::AntiDiagonal => (n - 1 - j, n - 1 - i),
+        }
+    }
+
+    /// Cells that have to be removed together with `(i, j)` on a board of
+    /// side `n`, including `(i, j)`.
+    fn orbit(self, n: usize, i: usize, j: usize) -> Vec<(usize, usize)> {
+        let mut orbit = vec![(i, j)];
+        loop {
+            let (i, j) = orbit[orbit.len() - 1];
+            let other = self.image(n, i, j);
+            if other == orbit[0] {
+                return orbit;
+            }
+            orbit.push(other);
+        }
+    }
+}
+
+impl std::str::FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Symmetry::None),
+            "rotational" => Ok(Symmetry::Rotational180),
+            "horizontal" => Ok(Symmetry::Horizontal),
+            "vertical" => Ok(Symmetry::Vertical),
+            "rotational90" => Ok(Symmetry::Rotational90),
+            "diagonal" => Ok(Symmetry::Diagonal),
+            "anti-diagonal" => Ok(Symmetry::AntiDiagonal),
+            _ => Err(format!("unknown symmetry {:?}", s)),
+        }
+    }
+}
+
+impl<const N: usize> Sudoku<N> {
+    /// Generates a random puzzle with a unique solution.
+    ///
+    /// A random full grid is built first, then clues are removed one at a
+    /// time in random order, putting a clue back whenever removing it would
+    /// allow more than one solution. The result is minimal, no single clue
+    /// can be removed without losing uniqueness.
+    ///
+    /// The solver is deterministic, so the puzzle only depends on `rng`: a
+    /// [`rand::rngs::StdRng`] created with `seed_from_u64` gives the same
+    /// puzzle for the same seed and the same version of this crate.
+    pub fn generate<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Self::generate_with_symmetry(rng, Symmetry::None)
+    }
+
+    /// Like [`Sudoku::generate`], but the clue positions have the given
+    /// symmetry.
+    ///
+    /// Clues are removed together with their mirror images and only if the
+    /// puzzle stays unique without all of them. The result is minimal among
+    /// symmetric puzzles, a single clue may still be removable on its own.
+    pub fn generate_with_symmetry<R: Rng + ?Sized>(rng: &mut R, symmetry: Symmetry) -> Self {
+        let grid = Self::random_full(rng);
+        let grid = Self::remove_clues(grid, rng, symmetry, |grid| {
+            Self::new(grid).has_unique_solution()
+        });
+        Self::new(grid)
+    }
+
+    /// Returns every symmetry of the clue positions, in the order of the
+    /// variants of [`Symmetry`] after [`Symmetry::None`], which is left out.
+    /// Only the givens are looked at, not their values, and a puzzle with
+    /// 90 degree symmetry also has [`Symmetry::Rotational180`].
+    pub fn clue_symmetry(&self) -> Vec<Symmetry> {
+        Symmetry::ALL
+            .iter()
+            .copied()
+            .filter(|&symmetry| {
+                (0..N * N).all(|c| {
+                    let (i, j) = (c / N, c % N);
+                    let (k, l) = symmetry.image(N, i, j);
+                    (self.givens[i][j] == 0) == (self.givens[k][l] == 0)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns a minimal puzzle with the same solution, keeping only some
+    /// of the givens.
+    ///
+    /// Like for [`Sudoku::generate`], the givens are removed one at a time
+    /// in random order, putting one back whenever removing it would allow
+    /// more than one solution. Variant rules and the box shape are kept, so
+    /// the puzzle only has to stay unique under them. The result is minimal
+    /// but not necessarily the smallest such puzzle, another order may
+    /// remove more givens. A puzzle that isn't unique to begin with comes
+    /// back unchanged. The grid of the result holds the remaining givens.
+    pub fn minimize_clues<R: Rng + ?Sized>(&self, rng: &mut R) -> Self {
+        let build = |grid| self.rebuild(grid);
+        let grid = Self::remove_clues(self.givens, rng, Symmetry::None, |grid| {
+            build(grid).has_unique_solution()
+        });
+        let mut minimized = build(grid);
+        minimized.set_symbols(self.symbols);
+        minimized
+    }
+
+    /// Removes the clues of `grid` in random order, together with their
+    /// mirror images under `symmetry`, as long as `unique` holds for the
+    /// grid without them.
+    fn remove_clues<R: Rng + ?Sized>(
+        mut grid: [[u32; N]; N],
+        rng: &mut R,
+        symmetry: Symmetry,
+        unique: impl Fn([[u32; N]; N]) -> bool,
+    ) -> [[u32; N]; N] {
+        let mut cells = (0..N * N).map(|c| (c / N, c % N)).collect::<Vec<_>>();
+        cells.shuffle(rng);
+        for (i, j) in cells {
+            if grid[i][j] == 0 {
+                // Already removed along with a mirror image
+                continue;
+            }
+            let orbit = symmetry.orbit(N, i, j);
+            let values = orbit.iter().map(|&(i, j)| grid[i][j]).collect::<Vec<_>>();
+            for &(i, j) in &orbit {
+                grid[i][j] = 0;
+            }
+            if !unique(grid) {
+                for (&(i, j), &value) in orbit.iter().zip(&values) {
+                    grid[i][j] = value;
+                }
+            }
+        }
+        grid
+    }
+
+    /// Builds a random full grid, e.g. the solution key of a puzzle. The
+    /// result only depends on `rng`, like for [`Sudoku::generate`].
+    ///
+    /// The boxes on the main diagonal don't constrain each other, so they are
+    /// filled with random permutations and the solver completes the rest of
+    /// the grid. A 9x9 grid can always be completed this way, on smaller
+    /// boards a different permutation is tried if it can't. The solver would
+    /// complete the same diagonal the same way every time, so the bands, the
+    /// rows within each band, the stacks and the columns within each stack
+    /// are shuffled afterwards, which keeps the grid valid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` isn't a perfect square, and so do [`Sudoku::generate`]
+    /// and [`Sudoku::generate_with_symmetry`].
+    pub fn random_full<R: Rng + ?Sized>(rng: &mut R) -> [[u32; N]; N] {
+        let b = BoxShape::square(N).rows;
+        let mut values = (1..=N as u32).collect::<Vec<u32>>();
+        let solved = loop {
+            let mut grid = [[0u32; N]; N];
+            for d in 0..b {
+                values.shuffle(rng);
+                for (l, &value) in values.iter().enumerate() {
+                    grid[b * d + l / b][b * d + l % b] = value;
+                }
+            }
+            let mut sudoku = Self::new(grid);
+            if sudoku.solve().is_ok() {
+                break *sudoku.grid();
+            }
+        };
+
+        let rows = line_order(rng, b);
+        let cols = line_order(rng, b);
+        let mut grid = [[0u32; N]; N];
+        for (i, &r) in rows.iter().enumerate() {
+            for (j, &c) in cols.iter().enumerate() {
+                grid[i][j] = solved[r][c];
+            }
+        }
+        debug_assert!(Self::is_valid_solution(&grid));
+        grid
+    }
+}
+
+/// A random order of the `b * b` rows (or columns) of a board with boxes of
+/// side `b` that keeps the bands (or stacks) together.
+fn line_order<R: Rng + ?Sized>(rng: &mut R, b: usize) -> Vec<usize> {
+    let mut bands = (0..b).collect::<Vec<_>>();
+    bands.shuffle(rng);
+    let mut order = vec![];
+    for band in bands {
+        let mut lines = (b * band..b * band + b).collect::<Vec<_>>();
+        lines.shuffle(rng);
+        order.extend(lines);
+    }
+    order
+}
@@ -0,0 +1,1369 @@
+//! A Sudoku solver that encodes the puzzle as a SAT formula and solves it
+//! with the [`rsat`] CDCL solver.
+
+mod canonical;
+mod constraints;
+mod encoding;
+mod generate;
+mod logic;
+mod parse;
+mod render;
+mod samurai;
+#[cfg(feature = "serde")]
+mod serialize;
+mod shape;
+mod symbols;
+mod template;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use constraints::{Cage, Constraints, Inequality, Thermo};
+pub use encoding::Encoding;
+pub use generate::Symmetry;
+pub use logic::{Difficulty, LogicalResult, SolvedBy, Step, Technique, Unit};
+pub use parse::{
+    format_compact, format_json, format_oneline, parse_json, parse_oneline, parse_oneline_with,
+    read_grid, read_grid_with, ParseGridError,
+};
+pub use samurai::{read_samurai, Samurai};
+pub use shape::BoxShape;
+pub use symbols::Symbols;
+pub use template::SudokuTemplate;
+
+use encoding::Encoder;
+use log::{debug, info, trace};
+use rsat::Lit;
+use rsat::Solution;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A Sudoku puzzle along with its SAT encoding.
+///
+/// `N` is the side of the board. The boxes are `sqrt(N)` cells wide unless
+/// a [`BoxShape`] is given: `Sudoku<4>`, `Sudoku` (9x9) and `Sudoku<16>`
+/// have square boxes, `Sudoku<6>` needs 2x3 boxes. It's the board side
+/// rather than the box side because stable Rust can't size arrays with
+/// `N * N` yet.
+pub struct Sudoku<const N: usize = 9> {
+    grid: [[u32; N]; N],
+    /// The grid as it was given, before solving.
+    givens: [[u32; N]; N],
+    /// Givens encoded as unit clauses, the others are passed to the solver
+    /// as assumptions, see [`Sudoku::set_given`].
+    fixed: [[u32; N]; N],
+    /// Cells set with [`Sudoku::constrain`], in the order they were set.
+    constrained: Vec<((usize, usize), u32)>,
+    solver: rsat::msat::Solver,
+    /// Set once a clause was already falsified when it was added, rsat drops
+    /// such clauses so the formula is known to be unsatisfiable.
+    contradiction: bool,
+    /// Clash between two givens found by [`Sudoku::validate_givens`].
+    conflict: Option<ConflictError>,
+    /// Variant rules the encoding was built with, kept to rebuild it.
+    constraints: Constraints,
+    /// How "at most one" constraints are encoded.
+    encoding: Encoding,
+    /// Shape of the boxes, `None` for the irregular regions of a Jigsaw
+    /// Sudoku.
+    box_shape: Option<BoxShape>,
+    /// Region of each cell, numbered like the boxes for a board with boxes.
+    regions: [[usize; N]; N],
+    /// Characters the values are printed with.
+    symbols: Symbols,
+    /// Every clause of the encoding, in the order it was added.
+    clauses: Vec<Vec<Lit>>,
+    /// Number of variables used by the encoding.
+    num_vars: usize,
+    /// Literal meaning cell `(i, j)` holds `k + 1`, its variable is
+    /// [`Sudoku::var_index`]`(i, j, k)`.
+    lits: [[[Lit; N]; N]; N],
+}
+
+/// Draws the grid with its box borders.
+///
+/// The default symbols stop at 35, so a board larger than that panics here,
+/// in [`Sudoku::to_compact`] and in [`Sudoku::to_oneline`].
+impl<const N: usize> std::fmt::Display for Sudoku<N> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.draw(f, false, false)
+    }
+}
+
+/// Parses a 9x9 puzzle in any format [`read_grid`] understands, including
+/// the output of `Display`.
+impl std::str::FromStr for Sudoku {
+    type Err = ParseGridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        read_grid(s.as_bytes()).map(Sudoku::new)
+    }
+}
+
+/// Any error of this crate, for callers that don't need to tell parsing
+/// and solving apart.
+#[derive(Debug, thiserror::Error)]
+pub enum SudokuError {
+    /// The puzzle couldn't be read.
+    #[error(transparent)]
+    Parse(#[from] ParseGridError),
+    /// The puzzle couldn't be solved.
+    #[error(transparent)]
+    Solve(#[from] SolveError),
+    /// Reading or writing something other than a puzzle failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Reasons why [`Sudoku::solve`] can fail.
+#[derive(Debug, thiserror::Error)]
+pub enum SolveError {
+    /// The puzzle has no solution.
+    #[error("puzzle has no solution")]
+    Unsatisfiable,
+    /// The solver gave up without proving either way.
+    #[error("solver couldn't decide the puzzle")]
+    Unknown,
+    /// The solution breaks the rules or contradicts the puzzle. `cell` is
+    /// the offending cell and its value if that's a cell set with
+    /// [`Sudoku::constrain`], which can't hold the value together with the
+    /// givens and the cells constrained before it. It is `None` if the
+    /// solver returned a model that breaks the rules or the givens.
+    #[error("{}", inconsistent(.cell))]
+    Inconsistent { cell: Option<((usize, usize), u32)> },
+    /// Two givens clash, so the puzzle can't have a solution.
+    #[error(transparent)]
+    Conflict(#[from] ConflictError),
+    /// Solving took longer than the time limit.
+    #[error("solver ran out of time")]
+    Timeout,
+}
+
+/// Message of [`SolveError::Inconsistent`].
+fn inconsistent(cell: &Option<((usize, usize), u32)>) -> String {
+    match cell {
+        Some(((i, j), value)) => format!("cell ({},{}) can't be {}", i, j, value),
+        None => "solver returned an invalid solution".to_string(),
+    }
+}
+
+/// DIMACS variables with the cell `(i, j)` and the value each of them
+/// stands for, see [`Sudoku::dimacs_with_legend`].
+pub type DimacsLegend = Vec<(usize, (usize, usize, u32))>;
+
+/// Size of the encoding and the state of the solver, see [`Sudoku::stats`].
+///
+/// rsat doesn't count decisions, conflicts or propagations, the number of
+/// learnt clauses is the closest it gets to a measure of search effort.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolverStats {
+    /// Variables of the encoding.
+    pub num_vars: usize,
+    /// Clauses of the encoding.
+    pub num_clauses: usize,
+    /// Clauses the solver has learnt from conflicts and still keeps.
+    pub learnt_clauses: usize,
+}
+
+/// Time spent in the parts of [`Sudoku::solve_timed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolveTiming {
+    /// The SAT search.
+    pub search: Duration,
+    /// Reading the grid off the model and checking it.
+    pub decoding: Duration,
+}
+
+/// The unit in which two givens clash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    Row,
+    Column,
+    Box,
+}
+
+/// Two givens with the same value in the same row, column or box.
+/// Coordinates are 0-based `(row, column)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error(
+    "cells ({},{}) and ({},{}) both contain {value} in {}",
+    .first.0, .first.1, .second.0, .second.1, self.unit()
+)]
+pub struct ConflictError {
+    pub kind: ConflictKind,
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+    pub value: u32,
+}
+
+impl ConflictError {
+    /// The unit both cells are in, for the error message.
+    fn unit(&self) -> String {
+        match self.kind {
+            ConflictKind::Row => format!("row {}", self.first.0),
+            ConflictKind::Column => format!("column {}", self.first.1),
+            ConflictKind::Box => "the same box".to_string(),
+        }
+    }
+}
+
+impl Sudoku {
+    /// Fewest givens a 9x9 puzzle with a unique solution can have under the
+    /// standard rules, variants can make do with less.
+    pub const MIN_UNIQUE_CLUES: usize = 17;
+}
+
+impl<const N: usize> Sudoku<N> {
+    /// Cells of every row, column and box (or region), in the order row
+    /// `l`, column `l`, box `l` for each `l`.
+    pub(crate) fn units(&self) -> impl Iterator<Item = Vec<(usize, usize)>> {
+        Self::units_of(&self.regions)
+    }
+
+    /// [`Sudoku::units`] for the given regions.
+    fn units_of(regions: &[[usize; N]; N]) -> impl Iterator<Item = Vec<(usize, usize)>> {
+        let cells = Self::region_cells(regions);
+        (0..N).flat_map(move |l| {
+            vec![
+                (0..N).map(|j| (l, j)).collect(),
+                (0..N).map(|i| (i, l)).collect(),
+                cells[l].clone(),
+            ]
+        })
+    }
+
+    /// Cells of each region in reading order, indexed by region.
+    fn region_cells(regions: &[[usize; N]; N]) -> Vec<Vec<(usize, usize)>> {
+        let mut cells = vec![vec![]; N];
+        for (i, row) in regions.iter().enumerate() {
+            for (j, &region) in row.iter().enumerate() {
+                cells[region].push((i, j));
+            }
+        }
+        cells
+    }
+
+    /// Region of each cell when the regions are boxes of the given shape.
+    fn box_regions(shape: BoxShape) -> [[usize; N]; N] {
+        let mut regions = [[0; N]; N];
+        for (i, row) in regions.iter_mut().enumerate() {
+            for (j, region) in row.iter_mut().enumerate() {
+                *region = shape.box_index(N, i, j);
+            }
+        }
+        regions
+    }
+
+    /// [`Sudoku::box_regions`] for a shape that must tile the board.
+    fn tiling_box_regions(shape: BoxShape) -> [[usize; N]; N] {
+        assert!(
+            shape.rows * shape.cols == N,
+            "{}x{} boxes don't tile a board of side {}",
+            shape.rows,
+            shape.cols,
+            N
+        );
+        Self::box_regions(shape)
+    }
+
+    /// Builds the SAT encoding for `grid`, where `0` marks an empty cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a value of `grid` is above `N`, and so does every other
+    /// constructor. Also panics if `N` isn't a perfect square, such boards
+    /// need [`Sudoku::with_box_shape`], and so do the other constructors
+    /// that take no [`BoxShape`].
+    pub fn new(grid: [[u32; N]; N]) -> Self {
+        Self::with_box_shape(grid, BoxShape::square(N))
+    }
+
+    /// Builds the SAT encoding for `grid` on a board whose boxes have the
+    /// given shape, e.g. 2x3 boxes for a 6x6 board.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shape.rows * shape.cols` isn't `N` or a value of `grid` is
+    /// above `N`.
+    pub fn with_box_shape(grid: [[u32; N]; N], shape: BoxShape) -> Self {
+        Self::with_options(grid, &Constraints::default(), Encoding::default(), shape)
+    }
+
+    /// Builds the SAT encoding for `grid` with the extra rules of a variant
+    /// on top of the standard ones.
+    pub fn with_constraints(grid: [[u32; N]; N], constraints: &Constraints) -> Self {
+        Self::with_options(grid, constraints, Encoding::default(), BoxShape::square(N))
+    }
+
+    /// Builds the SAT encoding for `grid` using `encoding` for the "at most
+    /// one" parts of the rules.
+    pub fn new_with_encoding(grid: [[u32; N]; N], encoding: Encoding) -> Self {
+        Self::with_options(grid, &Constraints::default(), encoding, BoxShape::square(N))
+    }
+
+    /// Builds the SAT encoding for `grid` with the rules of a variant, using
+    /// `encoding` for the "at most one" parts of the rules, on a board whose
+    /// boxes have the given shape.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shape.rows * shape.cols` isn't `N` or a value of `grid` is
+    /// above `N`.
+    pub fn with_options(
+        grid: [[u32; N]; N],
+        constraints: &Constraints,
+        encoding: Encoding,
+        shape: BoxShape,
+    ) -> Self {
+        let regions = Self::tiling_box_regions(shape);
+        Self::build(grid, constraints, encoding, Some(shape), regions)
+    }
+
+    /// Builds the SAT encoding for `grid` on a Jigsaw Sudoku, where the
+    /// boxes are replaced by irregular regions: `regions[i][j]` is the
+    /// region of cell `(i, j)`, from `0` to `N - 1`. Every row, column and
+    /// region contains each value once.
+    ///
+    /// # Panics
+    ///
+    /// Panics unless every region has exactly `N` cells, or if a value of
+    /// `grid` is above `N`.
+    pub fn with_regions(grid: [[u32; N]; N], regions: [[usize; N]; N]) -> Self {
+        let mut sizes = [0; N];
+        for &region in regions.iter().flatten() {
+            assert!(region < N, "region {} is out of range", region);
+            sizes[region] += 1;
+        }
+        for (region, &size) in sizes.iter().enumerate() {
+            assert!(
+                size == N,
+                "region {} has {} cells instead of {}",
+                region,
+                size,
+                N
+            );
+        }
+        Self::build(
+            grid,
+            &Constraints::default(),
+            Encoding::default(),
+            None,
+            regions,
+        )
+    }
+
+    /// Builds the SAT encoding for `grid` with the given rules and regions.
+    fn build(
+        grid: [[u32; N]; N],
+        constraints: &Constraints,
+        encoding: Encoding,
+        box_shape: Option<BoxShape>,
+        regions: [[usize; N]; N],
+    ) -> Self {
+        for &value in grid.iter().flatten() {
+            assert!(value as usize <= N, "value {} is out of range", value);
+        }
+        let mut sudoku = Sudoku {
+            grid,
+            givens: grid,
+            fixed: grid,
+            constrained: vec![],
+            solver: rsat::msat::Solver::new(rsat::msat::SolverOptions::default()),
+            contradiction: false,
+            conflict: Self::find_conflict(&grid, &regions).err(),
+            constraints: constraints.clone(),
+            encoding,
+            box_shape,
+            regions,
+            symbols: Symbols::default(),
+            clauses: vec![],
+            num_vars: 0,
+            lits: [[[Lit::new(0, false); N]; N]; N],
+        };
+
+        for (i, lits_i) in sudoku.lits.iter_mut().enumerate() {
+            for (j, lits_ij) in lits_i.iter_mut().enumerate() {
+                for (k, lits_ijk) in lits_ij.iter_mut().enumerate() {
+                    // Cell (i, j) is assigned k+1
+                    let var = sudoku.solver.new_var();
+                    debug_assert_eq!(var, Self::var_index(i, j, k));
+                    *lits_ijk = Lit::new(var, false);
+                }
+            }
+        }
+        let lits = sudoku.lits;
+        let region_cells = Self::region_cells(&regions);
+
+        // At most one value is assigned to each cell
+        // Each horizontal line contains k at most once
+        // Each vertical line contains k at most once
+        // Each box contains k at most once
+        match encoding {
+            Encoding::Pairwise => {
+                for i in 0..N {
+                    for j in 0..N {
+                        let region = &region_cells[regions[i][j]];
+                        let own = region
+                            .iter()
+                            .position(|&cell| cell == (i, j))
+                            .expect("a cell is in its region");
+                        for k in 0..N {
+                            for l in k + 1..N {
+                                // Cell(i, j) == k+1 => Cell(i, j) != l+1 for k < l
+                                sudoku.add_clause(vec![!lits[i][j][k], !lits[i][j][l]]);
+                            }
+                            for l in j + 1..N {
+                                // Cell(i, j) == k+1 => Cell(i, l) != k+1 for j < l
+                                sudoku.add_clause(vec![!lits[i][j][k], !lits[i][l][k]]);
+                            }
+                            for l in i + 1..N {
+                                // Cell(i, j) == k+1 => Cell(l, j) != k+1 for i < l
+                                sudoku.add_clause(vec![!lits[i][j][k], !lits[l][j][k]]);
+                            }
+
+                            // Pairs sharing a row or a column are already covered above
+                            for &(mod_i, mod_j) in &region[own + 1..] {
+                                if i != mod_i && j != mod_j {
+                                    // Cell(i, j) == k+1 => Cell(mod_i, mod_j) != k+1 for i != mod_i, j != mod_j
+                                    sudoku.add_clause(vec![!lits[i][j][k], !lits[mod_i][mod_j][k]]);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            Encoding::Sequential => {
+                for lits_ij in lits.iter().flatten() {
+                    sudoku.add_at_most_one(lits_ij);
+                }
+                for unit in sudoku.units().collect::<Vec<_>>() {
+                    sudoku.add_all_different(&lits, &unit);
+                }
+            }
+        }
+
+        for i in 0..N {
+            for j in 0..N {
+                // At least one of 1..=N is assigned to Cell(i, j)
+                sudoku.add_clause(lits[i][j].to_vec());
+
+                if grid[i][j] != 0 {
+                    // Unit clause for already assigned cells
+                    sudoku.add_clause(vec![lits[i][j][grid[i][j] as usize - 1]]);
+                }
+            }
+        }
+
+        sudoku.add_constraints(&lits, constraints);
+        sudoku.num_vars = sudoku.solver.n_vars();
+        debug!(
+            "encoded a {}x{} board: {} variables, {} clauses",
+            N,
+            N,
+            sudoku.num_vars,
+            sudoku.clauses.len()
+        );
+
+        sudoku
+    }
+
+    /// Checks that no value is given twice in the same row, column or box,
+    /// reporting the first clash in reading order. Boxes are square. Values
+    /// above `N` aren't looked at, the constructors panic on them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` isn't a perfect square, other boards are checked with
+    /// [`Sudoku::validate_givens_with_box_shape`].
+    pub fn validate_givens(grid: &[[u32; N]; N]) -> Result<(), ConflictError> {
+        Self::validate_givens_with_box_shape(grid, BoxShape::square(N))
+    }
+
+    /// [`Sudoku::validate_givens`] for boxes of the given shape.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shape.rows * shape.cols` isn't `N`.
+    pub fn validate_givens_with_box_shape(
+        grid: &[[u32; N]; N],
+        shape: BoxShape,
+    ) -> Result<(), ConflictError> {
+        Self::find_conflict(grid, &Self::tiling_box_regions(shape))
+    }
+
+    /// [`Sudoku::validate_givens`] for any regions, a clash within a region
+    /// is reported as one within a box.
+    fn find_conflict(grid: &[[u32; N]; N], regions: &[[usize; N]; N]) -> Result<(), ConflictError> {
+        for c1 in 0..N * N {
+            let (i, j) = (c1 / N, c1 % N);
+            let value = grid[i][j];
+            if value == 0 {
+                continue;
+            }
+            for c2 in (c1 + 1)..N * N {
+                let (i2, j2) = (c2 / N, c2 % N);
+                if grid[i2][j2] != value {
+                    continue;
+                }
+                let kind = if i == i2 {
+                    ConflictKind::Row
+                } else if j == j2 {
+                    ConflictKind::Column
+                } else if regions[i][j] == regions[i2][j2] {
+                    ConflictKind::Box
+                } else {
+                    continue;
+                };
+                return Err(ConflictError {
+                    kind,
+                    first: (i, j),
+                    second: (i2, j2),
+                    value,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if `grid` is completely filled with values in `1..=N`
+    /// and every row, column and box contains each of them once.
+    ///
+    /// This only looks at the grid, no solver is involved. Boxes are square
+    /// and variant rules aren't checked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` isn't a perfect square, other boards are checked with
+    /// [`Sudoku::is_valid_solution_with_box_shape`].
+    pub fn is_valid_solution(grid: &[[u32; N]; N]) -> bool {
+        Self::is_valid_solution_with_box_shape(grid, BoxShape::square(N))
+    }
+
+    /// [`Sudoku::is_valid_solution`] for boxes of the given shape.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shape.rows * shape.cols` isn't `N`.
+    pub fn is_valid_solution_with_box_shape(grid: &[[u32; N]; N], shape: BoxShape) -> bool {
+        Self::is_solution_of(grid, &Self::tiling_box_regions(shape))
+    }
+
+    /// [`Sudoku::is_valid_solution`] for any regions.
+    fn is_solution_of(grid: &[[u32; N]; N], regions: &[[usize; N]; N]) -> bool {
+        Self::units_of(regions).all(|unit| {
+            let mut seen = [false; N];
+            unit.iter().all(|&(i, j)| {
+                let value = grid[i][j] as usize;
+                (1..=N).contains(&value) && !std::mem::replace(&mut seen[value - 1], true)
+            })
+        })
+    }
+
+    /// Solves the puzzle, filling in every empty cell of the grid.
+    pub fn solve(&mut self) -> Result<(), SolveError> {
+        self.grid = self.solution()?;
+        Ok(())
+    }
+
+    /// Solves the puzzle and returns the solved grid, the grid of `self` is
+    /// left as it was. The solver still learns from the search, so it needs
+    /// `&mut self`.
+    pub fn solution(&mut self) -> Result<[[u32; N]; N], SolveError> {
+        self.check_solvable()?;
+        // Instant isn't available everywhere, e.g. on wasm, only time the
+        // search if it gets logged
+        let start = log::log_enabled!(log::Level::Info).then(Instant::now);
+        let solution = self.solver.solve(self.assumptions());
+        let result = self.check_model(solution).map_err(|e| self.blame(e));
+        if let Some(start) = start {
+            let outcome = match &result {
+                Ok(_) => "solved".to_string(),
+                Err(e) => e.to_string(),
+            };
+            info!("{} in {:?}", outcome, start.elapsed());
+        }
+        result
+    }
+
+    /// Like [`Sudoku::solve`], also returning how long the search and the
+    /// decoding took. This needs [`Instant`], which panics on targets
+    /// without a clock such as `wasm32-unknown-unknown`.
+    pub fn solve_timed(&mut self) -> Result<SolveTiming, SolveError> {
+        self.check_solvable()?;
+        let start = Instant::now();
+        let solution = self.solver.solve(self.assumptions());
+        let search = start.elapsed();
+        let start = Instant::now();
+        self.grid = self.check_model(solution).map_err(|e| self.blame(e))?;
+        Ok(SolveTiming {
+            search,
+            decoding: start.elapsed(),
+        })
+    }
+
+    /// Like [`Sudoku::solution`], with the cells in `extra` assumed to hold
+    /// their values on top of the givens, e.g. to ask whether the puzzle
+    /// can still be solved with 7 in the middle cell. The assumptions only
+    /// last for this call and no clauses are added, so the solver and what
+    /// it has learnt are reused as they are. Fails with
+    /// [`SolveError::Unsatisfiable`] if no solution agrees with `extra`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the values isn't in `1..=N`.
+    pub fn solve_assuming(
+        &mut self,
+        extra: &[((usize, usize), u32)],
+    ) -> Result<[[u32; N]; N], SolveError> {
+        self.check_solvable()?;
+        let mut assumptions = self.assumptions();
+        for &((i, j), value) in extra {
+            assert!(
+                (1..=N as u32).contains(&value),
+                "value {} is out of range",
+                value
+            );
+            assumptions.push(self.given_lit(i, j, value));
+        }
+        let solution = self.solver.solve(assumptions);
+        self.check_model(solution)
+    }
+
+    /// Sets cell `(i, j)` to the clue `value`, or clears it with `0`.
+    ///
+    /// Unlike the givens passed to the constructor, which are encoded as
+    /// unit clauses, clues set this way are passed to the solver as
+    /// assumptions. The same solver, along with everything it has learnt,
+    /// is reused when solving again, which is much cheaper than building a
+    /// new `Sudoku` after every edit. The grid is reset to the givens.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is above `N`, or if `(i, j)` holds one of the
+    /// givens passed to the constructor and `value` is different: those
+    /// are part of the encoding and can't be changed.
+    pub fn set_given(&mut self, i: usize, j: usize, value: u32) {
+        assert!(value as usize <= N, "value {} is out of range", value);
+        assert!(
+            self.fixed[i][j] == 0 || self.fixed[i][j] == value,
+            "cell ({}, {}) holds a given of the encoding",
+            i,
+            j
+        );
+        let mut givens = self.givens;
+        givens[i][j] = value;
+        self.reset_givens(givens);
+    }
+
+    /// Requires cell `(i, j)` to hold `value`, e.g. a value deduced by hand
+    /// that should be checked against the puzzle. Unlike [`Sudoku::set_given`]
+    /// the givens and the grid stay as they are, the cell is only filled in
+    /// by solving.
+    ///
+    /// Constrained cells are passed to the solver as assumptions, like the
+    /// clues of [`Sudoku::set_given`], and hold for every search until
+    /// [`Sudoku::clear_constraints`]. If they make the puzzle unsolvable,
+    /// [`Sudoku::solve`] fails with [`SolveError::Inconsistent`] naming the
+    /// first constrained cell, in the order they were set, that can't hold
+    /// its value together with the givens and the ones before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` isn't in `1..=N`.
+    pub fn constrain(&mut self, (i, j): (usize, usize), value: u32) {
+        assert!(
+            (1..=N as u32).contains(&value),
+            "value {} is out of range",
+            value
+        );
+        self.constrained.push(((i, j), value));
+    }
+
+    /// Drops every cell set with [`Sudoku::constrain`].
+    pub fn clear_constraints(&mut self) {
+        self.constrained.clear();
+    }
+
+    /// Turns [`SolveError::Unsatisfiable`] into [`SolveError::Inconsistent`]
+    /// if the givens alone are solvable, by adding the constrained cells one
+    /// at a time until the puzzle becomes unsolvable. Other errors are
+    /// returned as they are.
+    fn blame(&mut self, error: SolveError) -> SolveError {
+        if !matches!(error, SolveError::Unsatisfiable) || self.constrained.is_empty() {
+            return error;
+        }
+        let constrained = std::mem::take(&mut self.constrained);
+        let mut assumptions = self.assumptions();
+        let mut blamed = error;
+        if let Solution::Sat(_) = self.solver.solve(assumptions.clone()) {
+            for &((i, j), value) in &constrained {
+                assumptions.push(self.given_lit(i, j, value));
+                match self.solver.solve(assumptions.clone()) {
+                    Solution::Sat(_) => {}
+                    Solution::Unsat => {
+                        blamed = SolveError::Inconsistent {
+                            cell: Some(((i, j), value)),
+                        };
+                        break;
+                    }
+                    Solution::Unknown | Solution::Best(_) => break,
+                }
+            }
+        }
+        self.constrained = constrained;
+        blamed
+    }
+
+    /// Replaces the givens, and the grid with them.
+    pub(crate) fn reset_givens(&mut self, givens: [[u32; N]; N]) {
+        self.givens = givens;
+        self.grid = givens;
+        self.conflict = Self::find_conflict(&givens, &self.regions).err();
+    }
+
+    /// Builds the SAT encoding for `grid` again, with the rules, encoding
+    /// and regions of this puzzle.
+    pub(crate) fn rebuild(&self, grid: [[u32; N]; N]) -> Self {
+        Self::build(
+            grid,
+            &self.constraints,
+            self.encoding,
+            self.box_shape,
+            self.regions,
+        )
+    }
+
+    /// Variable meaning cell `(i, j)` holds `k + 1`. The cell variables
+    /// come first, numbered in reading order, followed by the auxiliary
+    /// variables of the encoding and the variants.
+    pub(crate) fn var_index(i: usize, j: usize, k: usize) -> usize {
+        N * N * i + N * j + k
+    }
+
+    /// Literal meaning cell `(i, j)` holds `value`.
+    fn given_lit(&self, i: usize, j: usize, value: u32) -> Lit {
+        self.lits[i][j][value as usize - 1]
+    }
+
+    /// Literals for the givens that aren't part of the encoding and the
+    /// constrained cells.
+    fn assumptions(&self) -> Vec<Lit> {
+        let mut assumptions = vec![];
+        for (c, (&value, &fixed)) in self
+            .givens
+            .iter()
+            .flatten()
+            .zip(self.fixed.iter().flatten())
+            .enumerate()
+        {
+            if value != 0 && fixed == 0 {
+                assumptions.push(self.given_lit(c / N, c % N, value));
+            }
+        }
+        for &((i, j), value) in &self.constrained {
+            assumptions.push(self.given_lit(i, j, value));
+        }
+        assumptions
+    }
+
+    /// Like [`Sudoku::solve`], but gives up with [`SolveError::Timeout`] once
+    /// `limit` has passed.
+    ///
+    /// rsat can't be interrupted, so the search runs on its own thread and
+    /// is abandoned on timeout: it keeps running in the background until it
+    /// finishes, while this puzzle gets a freshly built encoding.
+    pub fn solve_with_timeout(&mut self, limit: Duration) -> Result<(), SolveError> {
+        self.check_solvable()?;
+        let mut solver = std::mem::replace(
+            &mut self.solver,
+            rsat::msat::Solver::new(rsat::msat::SolverOptions::default()),
+        );
+        let assumptions = self.assumptions();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let solution = solver.solve(assumptions);
+            // The receiver is gone if we timed out, nobody needs the result
+            let _ = tx.send((solver, solution));
+        });
+        match rx.recv_timeout(limit) {
+            Ok((solver, solution)) => {
+                self.solver = solver;
+                self.grid = self.check_model(solution).map_err(|e| self.blame(e))?;
+                Ok(())
+            }
+            Err(_) => {
+                let (givens, symbols) = (self.givens, self.symbols);
+                let constrained = std::mem::take(&mut self.constrained);
+                *self = self.rebuild(self.fixed);
+                self.reset_givens(givens);
+                self.symbols = symbols;
+                self.constrained = constrained;
+                Err(SolveError::Timeout)
+            }
+        }
+    }
+
+    /// Fails early for puzzles known to be unsolvable without searching.
+    pub(crate) fn check_solvable(&self) -> Result<(), SolveError> {
+        if let Some(conflict) = self.conflict {
+            return Err(SolveError::Conflict(conflict));
+        }
+        if self.contradiction {
+            return Err(SolveError::Unsatisfiable);
+        }
+        Ok(())
+    }
+
+    /// Fills the grid in with `grid`, e.g. one of the solutions returned by
+    /// [`Sudoku::solve_all`], as if the solver had found it.
+    ///
+    /// Fails with [`SolveError::Inconsistent`] unless `grid` follows the
+    /// rules of the board and keeps the givens, without looking at variant
+    /// rules.
+    pub fn set_solution(&mut self, grid: [[u32; N]; N]) -> Result<(), SolveError> {
+        self.grid = self.check_solution(grid)?;
+        Ok(())
+    }
+
+    /// Returns `grid` if it follows the rules of the board and keeps the
+    /// givens.
+    fn check_solution(&self, grid: [[u32; N]; N]) -> Result<[[u32; N]; N], SolveError> {
+        let givens = self.givens.iter().flatten();
+        if !Self::is_solution_of(&grid, &self.regions)
+            || givens
+                .zip(grid.iter().flatten())
+                .any(|(&given, &value)| given != 0 && given != value)
+        {
+            return Err(SolveError::Inconsistent { cell: None });
+        }
+        Ok(grid)
+    }
+
+    /// Turns the result of a solver run into the solved grid, checking
+    /// that it is valid and keeps the givens.
+    fn check_model(&self, solution: Solution) -> Result<[[u32; N]; N], SolveError> {
+        match solution {
+            Solution::Sat(sol) => self.check_solution(Self::decode(&sol)),
+            Solution::Unsat => Err(SolveError::Unsatisfiable),
+            Solution::Unknown | Solution::Best(_) => Err(SolveError::Unknown),
+        }
+    }
+
+    /// Returns the smallest solution, the one coming first when the grids
+    /// are compared cell by cell in reading order. Unlike the solution
+    /// [`Sudoku::solution`] returns, it doesn't depend on how the solver
+    /// searches. The grid of `self` is left as it was.
+    ///
+    /// The empty cells are fixed in reading order, each to the smallest
+    /// value some solution agrees with. Only values below the one in the
+    /// last solution found need to be tried, each with a call to
+    /// [`Sudoku::solve_assuming`] that reuses what the solver has learnt.
+    pub fn solve_min(&mut self) -> Result<[[u32; N]; N], SolveError> {
+        let mut best = self.solution()?;
+        let mut fixed = vec![];
+        for c in 0..N * N {
+            let (i, j) = (c / N, c % N);
+            if self.givens[i][j] != 0 {
+                continue;
+            }
+            for value in 1..best[i][j] {
+                fixed.push(((i, j), value));
+                let result = self.solve_assuming(&fixed);
+                fixed.pop();
+                match result {
+                    Ok(grid) => {
+                        best = grid;
+                        break;
+                    }
+                    Err(SolveError::Unsatisfiable) => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            fixed.push(((i, j), best[i][j]));
+        }
+        Ok(best)
+    }
+
+    /// Counts the distinct solutions of the puzzle, stopping once `limit` of
+    /// them have been found.
+    pub fn count_solutions(&mut self, limit: usize) -> usize {
+        self.solutions().take(limit).count()
+    }
+
+    /// Returns every solution of the puzzle, or only the first `max` of them.
+    ///
+    /// Mind the limit on sparse grids, an empty grid has about 6.7e21
+    /// solutions. [`Sudoku::solutions`] finds them one at a time instead.
+    pub fn solve_all(&mut self, max: Option<usize>) -> Vec<[[u32; N]; N]> {
+        match max {
+            Some(max) => self.solutions().take(max).collect(),
+            None => self.solutions().collect(),
+        }
+    }
+
+    /// Returns an iterator over the distinct solutions of the puzzle, each
+    /// one searched for when `next` is called, so the caller can stop at
+    /// any time.
+    ///
+    /// Every solution found is excluded by a blocking clause guarded by a
+    /// fresh selector variable. The selector is switched off when the
+    /// iterator is dropped, so the puzzle can still be solved afterwards.
+    pub fn solutions(&mut self) -> Solutions<'_, N> {
+        let selector = Lit::new(self.solver.new_var(), false);
+        Solutions {
+            done: self.contradiction,
+            sudoku: self,
+            selector,
+            found: 0,
+        }
+    }
+
+    /// Reads the grid off a satisfying assignment.
+    fn decode(sol: &[bool]) -> [[u32; N]; N] {
+        let mut grid = [[0u32; N]; N];
+        for (i, grid_i) in grid.iter_mut().enumerate() {
+            for (j, grid_ij) in grid_i.iter_mut().enumerate() {
+                for k in 0..N {
+                    if sol[Self::var_index(i, j, k)] {
+                        *grid_ij = k as u32 + 1;
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    /// Returns true if the puzzle has exactly one solution.
+    ///
+    /// Stops searching as soon as a second solution is found and leaves the
+    /// solver usable, see [`Sudoku::count_solutions`].
+    pub fn has_unique_solution(&mut self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    /// Returns a minimal set of givens that can't all hold at once, or an
+    /// empty list if the puzzle is solvable.
+    ///
+    /// The rules are encoded again without any givens, which are passed as
+    /// assumptions instead. Givens are then dropped one at a time, keeping
+    /// only those without which the rest becomes solvable, so removing any
+    /// one of the returned givens makes the others consistent. If the rules
+    /// alone can't be satisfied, e.g. because of impossible cages, no given
+    /// is to blame and the list is empty.
+    pub fn explain_unsat(&mut self) -> Vec<((usize, usize), u32)> {
+        let mut probe = self.rebuild([[0; N]; N]);
+        if probe.contradiction {
+            return vec![];
+        }
+        let mut givens = (0..N * N)
+            .map(|c| (c / N, c % N))
+            .filter(|&(i, j)| self.givens[i][j] != 0)
+            .map(|(i, j)| ((i, j), self.givens[i][j]))
+            .collect::<Vec<_>>();
+        let mut unsat = |givens: &[((usize, usize), u32)]| {
+            let assumptions = givens
+                .iter()
+                .map(|&((i, j), value)| self.given_lit(i, j, value))
+                .collect();
+            matches!(probe.solver.solve(assumptions), Solution::Unsat)
+        };
+        if !unsat(&givens) {
+            return vec![];
+        }
+
+        let mut c = 0;
+        while c < givens.len() {
+            let given = givens.remove(c);
+            if !unsat(&givens) {
+                // Needed for the contradiction, keep it
+                givens.insert(c, given);
+                c += 1;
+            }
+        }
+        givens
+    }
+
+    /// Returns the first empty cell in reading order together with its value
+    /// in a solution, leaving the grid untouched.
+    ///
+    /// Returns `None` if the grid is full or the puzzle has no solution. On
+    /// a puzzle with several solutions the value comes from one of them.
+    pub fn hint(&mut self) -> Option<((usize, usize), u32)> {
+        let (i, j) = (0..N * N)
+            .map(|c| (c / N, c % N))
+            .find(|&(i, j)| self.grid[i][j] == 0)?;
+        self.check_solvable().ok()?;
+        match self.solver.solve(self.assumptions()) {
+            Solution::Sat(sol) => Some(((i, j), Self::decode(&sol)[i][j])),
+            Solution::Unsat | Solution::Unknown | Solution::Best(_) => None,
+        }
+    }
+
+    /// Returns every cell that is neither given nor constrained and whose
+    /// value is the same in all solutions, with that value, in reading
+    /// order. On a puzzle with a unique solution that is every such cell,
+    /// whether or not the puzzle has been solved already.
+    ///
+    /// A first solution gives each cell its only possible forced value.
+    /// Then each cell is asked in turn whether the puzzle can be solved
+    /// with that value ruled out, as an assumption so nothing is added to
+    /// the solver. A solution found that way also clears every other cell
+    /// where it differs from the first one. The grid is left untouched,
+    /// and an unsolvable puzzle has no forced cells.
+    pub fn forced_cells(&mut self) -> Vec<((usize, usize), u32)> {
+        let first = match self.solution() {
+            Ok(grid) => grid,
+            Err(_) => return vec![],
+        };
+        let mut forced = [[true; N]; N];
+        let mut cells = vec![];
+        for c in 0..N * N {
+            let (i, j) = (c / N, c % N);
+            let constrained = self.constrained.iter().any(|&(cell, _)| cell == (i, j));
+            if self.givens[i][j] != 0 || constrained || !forced[i][j] {
+                continue;
+            }
+            let mut assumptions = self.assumptions();
+            assumptions.push(!self.given_lit(i, j, first[i][j]));
+            match self.solver.solve(assumptions) {
+                Solution::Sat(sol) => {
+                    let other = Self::decode(&sol);
+                    for (c, (&a, &b)) in first
+                        .iter()
+                        .flatten()
+                        .zip(other.iter().flatten())
+                        .enumerate()
+                    {
+                        if a != b {
+                            forced[c / N][c % N] = false;
+                        }
+                    }
+                }
+                Solution::Unsat => cells.push(((i, j), first[i][j])),
+                // Not knowing doesn't make the value forced
+                Solution::Unknown | Solution::Best(_) => {}
+            }
+        }
+        cells
+    }
+
+    /// Returns the shape of the boxes, `None` for a Jigsaw Sudoku.
+    pub fn box_shape(&self) -> Option<BoxShape> {
+        self.box_shape
+    }
+
+    /// Returns the region of each cell, the index of its box unless built
+    /// with [`Sudoku::with_regions`].
+    pub fn regions(&self) -> &[[usize; N]; N] {
+        &self.regions
+    }
+
+    /// Returns the characters the values are printed with.
+    pub fn symbols(&self) -> Symbols {
+        self.symbols
+    }
+
+    /// Prints the values with `symbols` from now on, in `Display`,
+    /// [`Sudoku::to_compact`], [`Sudoku::to_oneline`] and the SVG image.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbols` can't show every value of the board, when `N` is
+    /// above [`Symbols::max_value`].
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        assert!(
+            N as u32 <= symbols.max_value(),
+            "{:?} can't show the values of a {}x{} board",
+            symbols,
+            N,
+            N
+        );
+        self.symbols = symbols;
+    }
+
+    /// Returns the grid as it was given, before solving. `0` marks an
+    /// empty cell.
+    pub fn givens(&self) -> &[[u32; N]; N] {
+        &self.givens
+    }
+
+    /// Returns true if cell `(i, j)` was given rather than filled in by the
+    /// solver.
+    pub fn is_given(&self, i: usize, j: usize) -> bool {
+        self.givens[i][j] != 0
+    }
+
+    /// Returns the number of givens, solving doesn't change it. A 9x9
+    /// puzzle needs at least [`Sudoku::MIN_UNIQUE_CLUES`] of them to have a
+    /// unique solution under the standard rules.
+    pub fn clue_count(&self) -> usize {
+        self.givens
+            .iter()
+            .flatten()
+            .filter(|&&value| value != 0)
+            .count()
+    }
+
+    /// Returns every cell that was empty in the givens but has a value now,
+    /// with that value, in reading order.
+    pub fn filled_cells(&self) -> Vec<((usize, usize), u32)> {
+        (0..N * N)
+            .map(|c| (c / N, c % N))
+            .filter(|&(i, j)| !self.is_given(i, j) && self.grid[i][j] != 0)
+            .map(|(i, j)| ((i, j), self.grid[i][j]))
+            .collect()
+    }
+
+    /// Returns the number of variables and clauses of the encoding,
+    /// `(num_vars, num_clauses)`, which stay the same while solving.
+    ///
+    /// An empty 9x9 board takes 729 variables and 10287 clauses with
+    /// [`Encoding::Pairwise`], 3321 and 7533 with [`Encoding::Sequential`].
+    /// Each given adds a unit clause.
+    pub fn encoding_size(&self) -> (usize, usize) {
+        (self.num_vars, self.clauses.len())
+    }
+
+    /// Returns the size of the encoding and how many clauses the solver has
+    /// learnt so far.
+    pub fn stats(&self) -> SolverStats {
+        SolverStats {
+            num_vars: self.num_vars,
+            num_clauses: self.clauses.len(),
+            learnt_clauses: self.solver.n_learnts(),
+        }
+    }
+
+    /// Returns the current grid, `0` marks an empty cell.
+    pub fn grid(&self) -> &[[u32; N]; N] {
+        &self.grid
+    }
+
+    /// Returns the encoding in DIMACS CNF format.
+    ///
+    /// Variable `N * N * i + N * j + k + 1` means cell `(i, j)` holds `k + 1`,
+    /// the givens are unit clauses.
+    pub fn to_dimacs(&self) -> String {
+        let mut out = format!("p cnf {} {}\n", self.num_vars, self.clauses.len());
+        for clause in &self.clauses {
+            out += &dimacs_clause(clause);
+            out += "\n";
+        }
+        out
+    }
+
+    /// Returns [`Sudoku::to_dimacs`] along with what its cell variables
+    /// mean: for each DIMACS variable `v`, in increasing order, the cell
+    /// `(i, j)` and the value it holds when `v` is true. The auxiliary
+    /// variables of the encoding and the variants are left out, a model
+    /// found by another SAT solver is decoded by looking up its true
+    /// variables that have an entry.
+    pub fn dimacs_with_legend(&self) -> (String, DimacsLegend) {
+        let mut legend = vec![];
+        for i in 0..N {
+            for j in 0..N {
+                for k in 0..N {
+                    legend.push((Self::var_index(i, j, k) + 1, (i, j, k as u32 + 1)));
+                }
+            }
+        }
+        (self.to_dimacs(), legend)
+    }
+
+    /// Returns the box drawing of `Display` with ANSI colors, givens in bold
+    /// blue and the cells filled in by the solver in green.
+    pub fn to_colored(&self) -> String {
+        let mut out = String::new();
+        self.draw(&mut out, true, false)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    /// Returns the box drawing of `Display` with the columns labelled `C1`
+    /// to `CN` across the top and the rows `R1` to `RN` down the side, the
+    /// coordinates [`Sudoku::hint`] is printed with by the command line
+    /// tool. The cells are widened to fit the column labels.
+    pub fn to_labeled(&self) -> String {
+        let mut out = String::new();
+        self.draw(&mut out, false, true)
+            .expect("writing to a String can't fail");
+        out
+    }
+
+    /// Writes the box drawing of the grid, with ANSI colors if `colored`
+    /// and row and column labels if `labeled`.
+    fn draw(&self, f: &mut impl std::fmt::Write, colored: bool, labeled: bool) -> std::fmt::Result {
+        // A cell is as wide as a column label, the row labels end in a space
+        let (width, margin) = if labeled {
+            (format!("C{}", N).len(), format!("R{} ", N).len())
+        } else {
+            (1, 0)
+        };
+        let shape = match self.box_shape {
+            Some(shape) => shape,
+            None => return self.draw_regions(f, colored, labeled, width, margin),
+        };
+        if labeled {
+            let mut header = " ".repeat(margin + 1);
+            for j in 0..N {
+                header += &format!(" {:>width$}", format!("C{}", j + 1), width = width);
+                if j % shape.cols == shape.cols - 1 {
+                    header += "  ";
+                }
+            }
+            writeln!(f, "{}", header.trim_end())?;
+        }
+        let border = " ".repeat(margin)
+            + &format!("+{}", "-".repeat(shape.cols * (width + 1) + 1)).repeat(N / shape.cols)
+            + "+";
+        writeln!(f, "{}", border)?;
+        for i in 0..N {
+            if labeled {
+                write!(f, "{:<margin$}", format!("R{}", i + 1), margin = margin)?;
+            }
+            write!(f, "|")?;
+            for j in 0..N {
+                write!(f, "{}", " ".repeat(width))?;
+                self.draw_cell(f, i, j, colored)?;
+                if j % shape.cols == shape.cols - 1 {
+                    write!(f, " |")?;
+                }
+            }
+            writeln!(f)?;
+            if i % shape.rows == shape.rows - 1 {
+                writeln!(f, "{}", border)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// [`Sudoku::draw`] for a Jigsaw Sudoku. Every cell gets a gap on
+    /// each side, drawn as a line where it borders another region.
+    fn draw_regions(
+        &self,
+        f: &mut impl std::fmt::Write,
+        colored: bool,
+        labeled: bool,
+        width: usize,
+        margin: usize,
+    ) -> std::fmt::Result {
+        let vertical = |i, j| self.edge_left_of(i, j);
+        let horizontal = |i, j| self.edge_above(i, j);
+
+        if labeled {
+            let mut header = " ".repeat(margin);
+            for j in 0..N {
+                header += &format!("  {:>width$} ", format!("C{}", j + 1), width = width);
+            }
+            writeln!(f, "{}", header.trim_end())?;
+        }
+        for i in 0..=N {
+            // The line above row i, with a corner wherever a line meets it
+            let mut line = " ".repeat(margin);
+            for j in 0..=N {
+                let up = i > 0 && vertical(i - 1, j);
+                let left = j > 0 && horizontal(i, j - 1);
+                let corner = up || left || vertical(i, j) || horizontal(i, j);
+                line.push(if corner { '+' } else { ' ' });
+                if j < N {
+                    let edge = if horizontal(i, j) { "-" } else { " " };
+                    line += &edge.repeat(width + 2);
+                }
+            }
+            writeln!(f, "{}", line.trim_end())?;
+            if i == N {
+                break;
+            }
+
+            if labeled {
+                write!(f, "{:<margin$}", format!("R{}", i + 1), margin = margin)?;
+            }
+            for j in 0..N {
+                write!(f, "{}", if vertical(i, j) { '|' } else { ' ' })?;
+                write!(f, "{}", " ".repeat(width))?;
+                self.draw_cell(f, i, j, colored)?;
+                write!(f, " ")?;
+            }
+            writeln!(f, "|")?;
+        }
+        Ok(())
+    }
+
+    /// Returns the current grid as `N` lines of `N` cells without borders,
+    /// `.` marks an empty cell.
+    pub fn to_compact(&self) -> String {
+        format_compact(&self.grid, self.symbols)
+    }
+
+    /// Returns the current grid as a single line of `N * N` cells in
+    /// row-major order, `.` marks an empty cell. This is the format
+    /// [`parse_oneline`] reads.
+    pub fn to_oneline(&self) -> String {
+        format_oneline(&self.grid, self.symbols)
+    }
+
+    /// Returns the current grid as a JSON array of `N` rows of `N` numbers,
+    /// `0` marks an empty cell.
+    pub fn to_json(&self) -> String {
+        format_json(&self.grid)
+    }
+}
+
+/// Iterator over the solutions of a puzzle, see [`Sudoku::solutions`].
+pub struct Solutions<'a, const N: usize> {
+    sudoku: &'a mut Sudoku<N>,
+    /// Guards the blocking clauses added so far.
+    selector: Lit,
+    /// Number of solutions returned so far.
+    found: usize,
+    /// Set once the solver found no more solutions.
+    done: bool,
+}
+
+impl<const N: usize> Iterator for Solutions<'_, N> {
+    type Item = [[u32; N]; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let sudoku = &mut *self.sudoku;
+        let mut assumptions = sudoku.assumptions();
+        assumptions.push(self.selector);
+        match sudoku.solver.solve(assumptions) {
+            Solution::Sat(sol) => {
+                self.found += 1;
+                // At least one cell must differ from this solution
+                let mut cl = vec![!self.selector];
+                for &lit in sudoku.lits.iter().flatten().flatten() {
+                    if sol[lit.var()] {
+                        cl.push(!lit);
+                    }
+                }
+                trace!("blocking solution {}: {}", self.found, dimacs_clause(&cl));
+                sudoku.solver.new_clause(cl);
+                Some(Sudoku::decode(&sol))
+            }
+            Solution::Unsat | Solution::Unknown | Solution::Best(_) => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
+impl<const N: usize> Drop for Solutions<'_, N> {
+    fn drop(&mut self) {
+        self.sudoku.solver.new_clause(vec![!self.selector]);
+    }
+}
+
+/// Writes `clause` as a line of DIMACS CNF, 1-based variables ending in `0`.
+fn dimacs_clause(clause: &[Lit]) -> String {
+    let mut out = String::new();
+    for lit in clause {
+        let var = lit.var() as i64 + 1;
+        out += &format!("{} ", if lit.sign() { -var } else { var });
+    }
+    out + "0"
+}
@@ -0,0 +1,556 @@
+use crate::{SolveError, Sudoku};
+
+/// Outcome of [`Sudoku::solve_logical`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogicalResult<const N: usize = 9> {
+    /// Every cell was filled in without guessing.
+    Solved,
+    /// No rule applies anymore, holds the cells filled in so far.
+    Stuck([[u32; N]; N]),
+    /// The rules ran into a cell or a unit with no possible value left.
+    Contradiction,
+}
+
+/// What finished the puzzle in [`Sudoku::solve_hybrid`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolvedBy {
+    /// The techniques of [`Sudoku::solve_logical`] alone.
+    Logic,
+    /// The SAT solver, starting from the cells the techniques filled in.
+    Search,
+}
+
+/// How hard a puzzle is for a person, see [`Sudoku::difficulty`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    /// Naked and hidden singles are enough.
+    Easy,
+    /// Naked or hidden pairs are needed as well.
+    Medium,
+    /// Needs search.
+    Hard,
+    /// Needs a lot of search.
+    Evil,
+}
+
+/// A row, column or box of the board, numbered from 0 in reading order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Row(usize),
+    Column(usize),
+    Box(usize),
+}
+
+impl Unit {
+    /// Unit `u` in the order of [`Sudoku::units`].
+    fn at(u: usize) -> Self {
+        match u % 3 {
+            0 => Unit::Row(u / 3),
+            1 => Unit::Column(u / 3),
+            _ => Unit::Box(u / 3),
+        }
+    }
+}
+
+/// Prints the unit numbered from 1, e.g. `box 2`.
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Unit::Row(l) => write!(f, "row {}", l + 1),
+            Unit::Column(l) => write!(f, "column {}", l + 1),
+            Unit::Box(l) => write!(f, "box {}", l + 1),
+        }
+    }
+}
+
+/// The techniques of [`Sudoku::solve_logical`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    NakedPair,
+    HiddenPair,
+}
+
+/// One deduction of [`Sudoku::explain_steps`]. Cells are 0-based
+/// `(row, column)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub technique: Technique,
+    /// The unit the deduction is about, `None` for a naked single.
+    pub unit: Option<Unit>,
+    /// The cell filled in by a single, or the two cells of a pair.
+    pub cells: Vec<(usize, usize)>,
+    /// The value placed by a single, or the two values of a pair.
+    pub values: Vec<u32>,
+    /// Candidates a pair ruled out, empty for a single.
+    pub eliminated: Vec<((usize, usize), u32)>,
+}
+
+/// Narrates the step with 1-based coordinates, e.g. `R3C4 is the only
+/// cell in box 2 that can be 8 (hidden single)`.
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = |&(i, j): &(usize, usize)| format!("R{}C{}", i + 1, j + 1);
+        let cells = self.cells.iter().map(cell).collect::<Vec<_>>();
+        let values = self.values.iter().map(u32::to_string).collect::<Vec<_>>();
+        let unit = self.unit.map(|unit| unit.to_string()).unwrap_or_default();
+        match self.technique {
+            Technique::NakedSingle => {
+                write!(f, "{} can only be {} (naked single)", cells[0], values[0])?
+            }
+            Technique::HiddenSingle => write!(
+                f,
+                "{} is the only cell in {} that can be {} (hidden single)",
+                cells[0], unit, values[0]
+            )?,
+            Technique::NakedPair => write!(
+                f,
+                "{} can only be {} (naked pair in {})",
+                cells.join(" and "),
+                values.join(" or "),
+                unit
+            )?,
+            Technique::HiddenPair => write!(
+                f,
+                "{} only fit in {} in {} (hidden pair)",
+                values.join(" and "),
+                cells.join(" and "),
+                unit
+            )?,
+        }
+        if !self.eliminated.is_empty() {
+            let removed = self
+                .eliminated
+                .iter()
+                .map(|(c, value)| format!("{} from {}", value, cell(c)))
+                .collect::<Vec<_>>();
+            write!(f, ", removing {}", removed.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl Difficulty {
+    /// Learnt clauses above which a puzzle that needs search is rated
+    /// [`Difficulty::Evil`].
+    pub const EVIL_LEARNT_CLAUSES: usize = 50;
+}
+
+impl std::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Evil => write!(f, "evil"),
+        }
+    }
+}
+
+impl<const N: usize> Sudoku<N> {
+    /// Solves the puzzle without search, the way a person would with
+    /// naked and hidden singles and pairs.
+    ///
+    /// A naked single is a cell with only one candidate left, a hidden
+    /// single a value with only one possible cell in a row, column or box.
+    /// Both are filled in until neither applies. Then pairs eliminate
+    /// candidates: two cells of a unit with the same two candidates left
+    /// (a naked pair) take both values, and two values that fit in the
+    /// same two cells of a unit only (a hidden pair) leave no room for
+    /// other values there. After every elimination the singles get another
+    /// go. This doesn't use the SAT solver at all. On success the grid
+    /// holds the solution like after [`Sudoku::solve`], otherwise it is
+    /// left untouched.
+    pub fn solve_logical(&mut self) -> LogicalResult<N> {
+        match self.apply_techniques(true) {
+            Some(grid) if is_full(&grid) => {
+                self.grid = grid;
+                LogicalResult::Solved
+            }
+            Some(grid) => LogicalResult::Stuck(grid),
+            None => LogicalResult::Contradiction,
+        }
+    }
+
+    /// Like [`Sudoku::solve_logical`], returning the deductions in the order
+    /// they were made: the singles of a round in reading order, unit by
+    /// unit for hidden singles, then the pairs once singles run out. On
+    /// success the grid holds the solution, otherwise it is left untouched
+    /// and the steps end where the techniques got stuck or ran into a
+    /// contradiction.
+    pub fn explain_steps(&mut self) -> Vec<Step> {
+        let mut steps = vec![];
+        if let Some(grid) = self.deduce(true, false, &mut steps) {
+            if is_full(&grid) {
+                self.grid = grid;
+            }
+        }
+        steps
+    }
+
+    /// Solves the puzzle with [`Sudoku::solve_logical`] first and, if that
+    /// gets stuck, lets the SAT solver finish from the cells filled in so
+    /// far, which are passed to it as assumptions like the clues of
+    /// [`Sudoku::set_given`]. Returns which of the two finished it.
+    ///
+    /// Puzzles a person could solve with these techniques never reach the
+    /// solver, and the others still always get solved. It isn't faster
+    /// than [`Sudoku::solve`] though: the techniques propagate over the
+    /// whole clause list, which costs more than the search rsat needs on an
+    /// easy puzzle. The grid holds the solution afterwards, the givens stay
+    /// as they were.
+    pub fn solve_hybrid(&mut self) -> Result<SolvedBy, SolveError> {
+        self.check_solvable()?;
+        let grid = match self.solve_logical() {
+            LogicalResult::Solved => return Ok(SolvedBy::Logic),
+            LogicalResult::Stuck(grid) => grid,
+            LogicalResult::Contradiction => return Err(SolveError::Unsatisfiable),
+        };
+        let deduced = (0..N * N)
+            .map(|c| (c / N, c % N))
+            .filter(|&(i, j)| grid[i][j] != 0 && self.grid[i][j] == 0)
+            .map(|(i, j)| ((i, j), grid[i][j]))
+            .collect::<Vec<_>>();
+        self.grid = self.solve_assuming(&deduced)?;
+        Ok(SolvedBy::Search)
+    }
+
+    /// Returns true if [`Sudoku::solve_logical`] gets stuck, so solving the
+    /// puzzle takes guessing: the puzzles [`Sudoku::difficulty`] rates hard
+    /// or evil. A contradiction found by the techniques needs no guessing
+    /// either and gives false. The grid is left untouched.
+    pub fn requires_guessing(&self) -> bool {
+        match self.apply_techniques(true) {
+            Some(grid) => !is_full(&grid),
+            None => false,
+        }
+    }
+
+    /// Rates how hard the puzzle is for a person.
+    ///
+    /// The rating is a heuristic tied to the techniques implemented here:
+    /// [`Difficulty::Easy`] puzzles fall to naked and hidden singles alone,
+    /// [`Difficulty::Medium`] ones also need naked or hidden pairs. Anything
+    /// else has to be searched, and is [`Difficulty::Evil`] rather than
+    /// [`Difficulty::Hard`] if a fresh solver learns more than
+    /// [`Difficulty::EVIL_LEARNT_CLAUSES`] clauses on the way. The grid is
+    /// left untouched.
+    pub fn difficulty(&mut self) -> Result<Difficulty, SolveError> {
+        self.check_solvable()?;
+        let grid = self
+            .apply_techniques(false)
+            .ok_or(SolveError::Unsatisfiable)?;
+        if is_full(&grid) {
+            return Ok(Difficulty::Easy);
+        }
+        let grid = self
+            .apply_techniques(true)
+            .ok_or(SolveError::Unsatisfiable)?;
+        if is_full(&grid) {
+            return Ok(Difficulty::Medium);
+        }
+
+        let mut probe = self.rebuild(self.fixed);
+        probe.reset_givens(self.givens);
+        probe.solve()?;
+        if probe.stats().learnt_clauses > Difficulty::EVIL_LEARNT_CLAUSES {
+            Ok(Difficulty::Evil)
+        } else {
+            Ok(Difficulty::Hard)
+        }
+    }
+
+    /// Fills in singles, and eliminates naked and hidden pairs if `pairs`,
+    /// until none of them applies. Returns the grid reached or `None` on a
+    /// contradiction.
+    fn apply_techniques(&self, pairs: bool) -> Option<[[u32; N]; N]> {
+        self.deduce(pairs, true, &mut vec![])
+    }
+
+    /// [`Sudoku::apply_techniques`], recording every deduction in `steps`.
+    /// Unless `chain`, a round of naked singles only fills in the cells
+    /// that are singles in the grid as it is, see
+    /// [`Sudoku::propagate_with`], so that each step follows from the
+    /// earlier ones. That takes more rounds to reach the same grid.
+    fn deduce(&self, pairs: bool, chain: bool, steps: &mut Vec<Step>) -> Option<[[u32; N]; N]> {
+        let mut grid = self.grid;
+        // Variables ruled out by naked pairs
+        let mut excluded = vec![];
+        loop {
+            let assignment = self.propagate_with(&grid, &excluded, chain)?;
+            let possible =
+                |i: usize, j: usize, k: usize| assignment[Self::var_index(i, j, k)] != Some(false);
+            let mut progress = false;
+
+            // Naked singles, propagation already forced their value
+            for (c, value) in grid.iter_mut().flatten().enumerate() {
+                if *value == 0 {
+                    if let Some(k) =
+                        (0..N).find(|&k| assignment[Self::var_index(c / N, c % N, k)] == Some(true))
+                    {
+                        *value = k as u32 + 1;
+                        progress = true;
+                        steps.push(Step {
+                            technique: Technique::NakedSingle,
+                            unit: None,
+                            cells: vec![(c / N, c % N)],
+                            values: vec![*value],
+                            eliminated: vec![],
+                        });
+                    }
+                }
+            }
+
+            // Hidden singles
+            for (u, unit) in self.units().enumerate() {
+                for k in 0..N {
+                    let mut cells = unit.iter().filter(|&&(i, j)| possible(i, j, k));
+                    match (cells.next(), cells.next()) {
+                        (None, _) => return None,
+                        (Some(&(i, j)), None) if grid[i][j] == 0 => {
+                            grid[i][j] = k as u32 + 1;
+                            progress = true;
+                            steps.push(Step {
+                                technique: Technique::HiddenSingle,
+                                unit: Some(Unit::at(u)),
+                                cells: vec![(i, j)],
+                                values: vec![k as u32 + 1],
+                                eliminated: vec![],
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            // Naked pairs, only once singles are exhausted
+            if pairs && !progress {
+                for (u, unit) in self.units().enumerate() {
+                    let candidates = unit
+                        .iter()
+                        .map(|&(i, j)| (0..N).filter(|&k| possible(i, j, k)).collect::<Vec<_>>())
+                        .collect::<Vec<_>>();
+                    for (p, pair) in candidates.iter().enumerate() {
+                        if pair.len() != 2 {
+                            continue;
+                        }
+                        // Two cells sharing the same two candidates take both
+                        // values, the other cells of the unit can't have them
+                        let partner =
+                            match candidates[p + 1..].iter().position(|other| other == pair) {
+                                Some(q) => p + 1 + q,
+                                None => continue,
+                            };
+                        let mut eliminated = vec![];
+                        for (c, &(i, j)) in unit.iter().enumerate() {
+                            if c == p || c == partner {
+                                continue;
+                            }
+                            for &k in pair {
+                                let var = Self::var_index(i, j, k);
+                                if possible(i, j, k) && !excluded.contains(&var) {
+                                    excluded.push(var);
+                                    eliminated.push(((i, j), k as u32 + 1));
+                                }
+                            }
+                        }
+                        if !eliminated.is_empty() {
+                            progress = true;
+                            steps.push(Step {
+                                technique: Technique::NakedPair,
+                                unit: Some(Unit::at(u)),
+                                cells: vec![unit[p], unit[partner]],
+                                values: pair.iter().map(|&k| k as u32 + 1).collect(),
+                                eliminated,
+                            });
+                        }
+                    }
+
+                    // Hidden pairs, starting from the cells each value fits in
+                    let places = (0..N)
+                        .map(|k| {
+                            (0..N)
+                                .filter(|&c| candidates[c].contains(&k))
+                                .collect::<Vec<_>>()
+                        })
+                        .collect::<Vec<_>>();
+                    for (k1, cells) in places.iter().enumerate() {
+                        if cells.len() != 2 {
+                            continue;
+                        }
+                        // Two values confined to the same two cells take
+                        // both of them, the cells can't hold anything else
+                        let k2 = match places[k1 + 1..].iter().position(|other| other == cells) {
+                            Some(q) => k1 + 1 + q,
+                            None => continue,
+                        };
+                        let mut eliminated = vec![];
+                        for &c in cells {
+                            let (i, j) = unit[c];
+                            for &k in &candidates[c] {
+                                let var = Self::var_index(i, j, k);
+                                if k != k1 && k != k2 && !excluded.contains(&var) {
+                                    excluded.push(var);
+                                    eliminated.push(((i, j), k as u32 + 1));
+                                }
+                            }
+                        }
+                        if !eliminated.is_empty() {
+                            progress = true;
+                            steps.push(Step {
+                                technique: Technique::HiddenPair,
+                                unit: Some(Unit::at(u)),
+                                cells: cells.iter().map(|&c| unit[c]).collect(),
+                                values: vec![k1 as u32 + 1, k2 as u32 + 1],
+                                eliminated,
+                            });
+                        }
+                    }
+                }
+            }
+
+            if !progress {
+                return Some(grid);
+            }
+        }
+    }
+
+    /// Returns the values still possible in each cell, a given maps to just
+    /// itself.
+    ///
+    /// The candidates come from unit propagation over the encoding, starting
+    /// from the givens, so they take variant rules into account and drop
+    /// values ruled out by cells that are forced along the way. This doesn't
+    /// touch the SAT solver. If the givens contradict each other every cell
+    /// gets an empty list.
+    pub fn candidates(&self) -> [[Vec<u32>; N]; N] {
+        let assignment = self.propagate(&self.grid, &[]);
+        std::array::from_fn(|i| {
+            std::array::from_fn(|j| match &assignment {
+                Some(assignment) => (0..N)
+                    .filter(|&k| assignment[Self::var_index(i, j, k)] != Some(false))
+                    .map(|k| k as u32 + 1)
+                    .collect(),
+                None => vec![],
+            })
+        })
+    }
+
+    /// Returns the empty cell with the most candidates among those naked
+    /// and hidden singles can't fill, the one to look at when a person gets
+    /// stuck. Ties go to the first cell in reading order.
+    ///
+    /// The candidates are those of [`Sudoku::candidates`] once the singles
+    /// are filled in. `None` if singles solve the puzzle, or if it is full
+    /// or contradicts itself.
+    pub fn hardest_cell(&self) -> Option<(usize, usize)> {
+        let grid = self.apply_techniques(false)?;
+        let assignment = self.propagate(&grid, &[])?;
+        let mut hardest: Option<((usize, usize), usize)> = None;
+        for (i, row) in grid.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                if value != 0 {
+                    continue;
+                }
+                let count = (0..N)
+                    .filter(|&k| assignment[Self::var_index(i, j, k)] != Some(false))
+                    .count();
+                if hardest.is_none_or(|(_, most)| count > most) {
+                    hardest = Some(((i, j), count));
+                }
+            }
+        }
+        hardest.map(|(cell, _)| cell)
+    }
+
+    /// Runs unit propagation over the encoding with the values of `grid`
+    /// assigned and the variables in `excluded` set to false, returning the
+    /// value of every variable that got one or `None` on a conflict.
+    pub(crate) fn propagate(
+        &self,
+        grid: &[[u32; N]; N],
+        excluded: &[usize],
+    ) -> Option<Vec<Option<bool>>> {
+        self.propagate_with(grid, excluded, true)
+    }
+
+    /// [`Sudoku::propagate`], except that unless `chain` a cell found to
+    /// hold a value doesn't propagate any further. Its value is only set
+    /// once propagation is done, so the cells its value rules out keep
+    /// their candidates.
+    fn propagate_with(
+        &self,
+        grid: &[[u32; N]; N],
+        excluded: &[usize],
+        chain: bool,
+    ) -> Option<Vec<Option<bool>>> {
+        // Cell variables found to be true and held back if not `chain`
+        let mut held = vec![];
+        let mut assignment = vec![None; self.num_vars];
+        for (c, &value) in grid.iter().flatten().enumerate() {
+            if value != 0 {
+                assignment[Self::var_index(c / N, c % N, value as usize - 1)] = Some(true);
+            }
+        }
+        for &var in excluded {
+            if assignment[var] == Some(true) {
+                return None;
+            }
+            assignment[var] = Some(false);
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for clause in &self.clauses {
+                let mut unassigned = None;
+                let mut open = 0;
+                let mut satisfied = false;
+                for &lit in clause {
+                    match assignment[lit.var()] {
+                        // A negated literal is true when its variable is false
+                        Some(value) if value != lit.sign() => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(_) => {}
+                        None => {
+                            unassigned = Some(lit);
+                            open += 1;
+                        }
+                    }
+                }
+                if satisfied {
+                    continue;
+                }
+                match (open, unassigned) {
+                    (0, _) => return None,
+                    (1, Some(lit))
+                        if !chain
+                            && !lit.sign()
+                            && lit.var() < N * N * N
+                            && !held.contains(&lit.var()) =>
+                    {
+                        held.push(lit.var());
+                    }
+                    (1, Some(lit)) if !chain && !lit.sign() && lit.var() < N * N * N => {}
+                    (1, Some(lit)) => {
+                        assignment[lit.var()] = Some(!lit.sign());
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for var in held {
+            assignment[var] = Some(true);
+        }
+        Some(assignment)
+    }
+}
+
+/// Returns true if no cell of `grid` is empty.
+fn is_full<const N: usize>(grid: &[[u32; N]; N]) -> bool {
+    grid.iter().flatten().all(|&value| value != 0)
+}
@@ -1,136 +1,1029 @@
-use rsat::Lit;
-use rsat::Solution;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::io::{BufRead, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use structopt::StructOpt;
+use sudoku_solver::{
+    format_compact, format_json, format_oneline, parse_json, parse_oneline, read_grid,
+    read_samurai, Constraints, ParseGridError, Samurai, SolveError, Sudoku, SudokuError,
+    SudokuTemplate, Symbols, Symmetry,
+};
 
-struct Sudoku {
-    grid: [[u32; 9]; 9],
-    solver: rsat::msat::Solver,
+#[derive(Debug, StructOpt)]
+#[structopt(
+    name = "sudoku_solver",
+    about = "Solves Sudoku puzzles using a SAT solver"
+)]
+struct Opt {
+    /// The puzzle as a single line of 81 cells, read instead of stdin
+    #[structopt(conflicts_with = "file")]
+    puzzle: Option<String>,
+    /// Read the puzzle from this file instead of stdin
+    #[structopt(short, long, parse(from_os_str))]
+    file: Option<PathBuf>,
+    /// Input format: auto (nine lines or a single line) or json
+    #[structopt(long = "input-format", default_value = "auto")]
+    input_format: InputFormat,
+    /// Output format: pretty, labeled (pretty with R/C labels), compact (no
+    /// borders), json, svg, html or latex
+    #[structopt(long, default_value = "pretty")]
+    format: Format,
+    /// Print a newly generated puzzle instead of solving one
+    #[structopt(long)]
+    generate: bool,
+    /// Seed for --generate, the same seed always gives the same puzzle
+    #[structopt(long)]
+    seed: Option<u64>,
+    /// Symmetry of the clues for --generate: none, rotational, rotational90, horizontal, vertical,
+    /// diagonal or anti-diagonal
+    #[structopt(long, default_value = "none")]
+    symmetry: Symmetry,
+    /// Extra rules to apply, can be repeated: x (both diagonals), windoku, anti-knight, anti-king,
+    /// sandwich
+    #[structopt(long = "variant")]
+    variants: Vec<Variant>,
+    /// Sandwich clues of the rows for --variant sandwich, nine comma-separated sums, _ for none
+    #[structopt(long = "row-sums", default_value = "_,_,_,_,_,_,_,_,_")]
+    row_sums: SandwichSums,
+    /// Sandwich clues of the columns for --variant sandwich, like --row-sums
+    #[structopt(long = "col-sums", default_value = "_,_,_,_,_,_,_,_,_")]
+    col_sums: SandwichSums,
+    /// Solve one 81-character puzzle per line, printing one solution per line
+    #[structopt(long)]
+    batch: bool,
+    /// Edit and query the puzzle from --file, or an empty grid, with commands read from stdin
+    #[structopt(long)]
+    interactive: bool,
+    /// Only convert the puzzle from one format to another without solving: oneline, grid
+    /// (nine lines) or json
+    #[structopt(long, number_of_values = 2, value_names = &["from", "to"])]
+    transcode: Vec<GridFormat>,
+    /// Solve a Samurai Sudoku given as 21 lines of the 21x21 square
+    #[structopt(long)]
+    samurai: bool,
+    /// Number of threads solving puzzles in --batch mode
+    #[structopt(long, default_value = "1")]
+    jobs: usize,
+    /// Give up on a puzzle after this many milliseconds
+    #[structopt(long)]
+    timeout: Option<u64>,
+    /// Print the SAT encoding in DIMACS CNF format instead of solving
+    #[structopt(long)]
+    dimacs: bool,
+    /// Don't color the givens and the solved cells in pretty output
+    #[structopt(long = "no-color")]
+    no_color: bool,
+    /// Only print whether the puzzle is unsolvable, unique or has multiple solutions
+    #[structopt(long)]
+    check: bool,
+    /// Only print the number of solutions, counting up to this many (default 1000)
+    #[structopt(long)]
+    count: Option<Option<usize>>,
+    /// Print every solution, up to this many (default 100)
+    #[structopt(long)]
+    all: Option<Option<usize>>,
+    /// Only check that the grid in this file solves the puzzle, printing valid or invalid.
+    /// Variant rules aren't checked
+    #[structopt(long, parse(from_os_str))]
+    verify: Option<PathBuf>,
+    /// Only print how hard the puzzle is: easy, medium, hard or evil
+    #[structopt(long)]
+    rate: bool,
+    /// Only print the canonical form of the puzzle as a single line, the same for equivalent puzzles
+    #[structopt(long)]
+    canonical: bool,
+    /// Print the value of one empty cell instead of the whole solution
+    #[structopt(long)]
+    hint: bool,
+    /// Print the cells filled in by the solver instead of the solved grid
+    #[structopt(long)]
+    diff: bool,
+    /// Only narrate how naked and hidden singles and pairs solve the puzzle, step by step
+    #[structopt(long)]
+    explain: bool,
+    /// Only print the values still possible in each empty cell, as a grid of pencil marks
+    #[structopt(long)]
+    candidates: bool,
+    /// Print the puzzle and its solution next to each other
+    #[structopt(long = "side-by-side")]
+    side_by_side: bool,
+    /// Print the size of the encoding and solver statistics to stderr after solving
+    #[structopt(long)]
+    stats: bool,
+    /// Print the time spent encoding, searching and decoding to stderr after solving
+    #[structopt(long)]
+    timing: bool,
 }
 
-impl std::fmt::Display for Sudoku {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "+-------+-------+-------+")?;
-        for i in 0..9 {
-            write!(f, "|")?;
-            for j in 0..9 {
-                if self.grid[i][j] == 0 {
-                    write!(f, " _")?;
-                } else {
-                    write!(f, " {}", self.grid[i][j])?;
-                }
-                if j == 2 || j == 5 || j == 8 {
-                    write!(f, " |")?;
-                }
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Variant {
+    X,
+    Windoku,
+    AntiKnight,
+    AntiKing,
+    Sandwich,
+}
+
+impl std::str::FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Variant::X),
+            "windoku" => Ok(Variant::Windoku),
+            "anti-knight" => Ok(Variant::AntiKnight),
+            "anti-king" => Ok(Variant::AntiKing),
+            "sandwich" => Ok(Variant::Sandwich),
+            _ => Err(format!("unknown variant {:?}", s)),
+        }
+    }
+}
+
+/// Sandwich clues of the nine rows or columns.
+#[derive(Debug, Clone, PartialEq)]
+struct SandwichSums(Vec<Option<u32>>);
+
+impl std::str::FromStr for SandwichSums {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sums = s
+            .split(',')
+            .map(|sum| match sum.trim() {
+                "_" | "" => Ok(None),
+                sum => sum
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| format!("invalid sum {:?}", sum)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if sums.len() != 9 {
+            return Err(format!("expected 9 sums, found {}", sums.len()));
+        }
+        Ok(SandwichSums(sums))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InputFormat {
+    Auto,
+    Json,
+}
+
+impl std::str::FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(InputFormat::Auto),
+            "json" => Ok(InputFormat::Json),
+            _ => Err(format!("unknown input format {:?}", s)),
+        }
+    }
+}
+
+/// Plain formats of a grid, for `--transcode`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum GridFormat {
+    Oneline,
+    Grid,
+    Json,
+}
+
+impl GridFormat {
+    fn read<R: BufRead>(self, mut reader: R) -> Result<[[u32; 9]; 9], ParseGridError> {
+        let mut s = String::new();
+        reader.read_to_string(&mut s)?;
+        match self {
+            GridFormat::Oneline => {
+                let line = s.lines().find(|line| !line.trim().is_empty());
+                parse_oneline(line.unwrap_or(""))
+            }
+            GridFormat::Grid => read_grid(s.as_bytes()),
+            GridFormat::Json => parse_json(&s),
+        }
+    }
+
+    fn write(self, grid: &[[u32; 9]; 9]) -> String {
+        match self {
+            GridFormat::Oneline => format_oneline(grid, Symbols::default()) + "\n",
+            GridFormat::Grid => format_compact(grid, Symbols::default()),
+            GridFormat::Json => format_json(grid) + "\n",
+        }
+    }
+}
+
+impl std::str::FromStr for GridFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "oneline" => Ok(GridFormat::Oneline),
+            "grid" => Ok(GridFormat::Grid),
+            "json" => Ok(GridFormat::Json),
+            _ => Err(format!("unknown grid format {:?}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Pretty,
+    Labeled,
+    Compact,
+    Json,
+    Svg,
+    Html,
+    Latex,
+}
+
+impl std::str::FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pretty" => Ok(Format::Pretty),
+            "labeled" => Ok(Format::Labeled),
+            "compact" => Ok(Format::Compact),
+            "json" => Ok(Format::Json),
+            "svg" => Ok(Format::Svg),
+            "html" => Ok(Format::Html),
+            "latex" => Ok(Format::Latex),
+            _ => Err(format!("unknown format {:?}", s)),
+        }
+    }
+}
+
+/// Exit code for a solved puzzle, or any other mode that succeeded.
+const EXIT_SUCCESS: i32 = 0;
+/// Exit code for a puzzle without a solution, or an invalid `--verify`.
+const EXIT_UNSATISFIABLE: i32 = 1;
+/// Exit code for a puzzle with several solutions in `--check` mode.
+const EXIT_MULTIPLE: i32 = 2;
+/// Exit code for input that couldn't be read or parsed.
+const EXIT_INVALID_INPUT: i32 = 3;
+/// Exit code for a puzzle the solver couldn't decide, e.g. on a timeout.
+const EXIT_UNKNOWN: i32 = 4;
+
+fn main() {
+    env_logger::init();
+    let opt = Opt::from_args();
+    let code = match run(&opt) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            exit_code(&e)
+        }
+    };
+    std::process::exit(code);
+}
+
+/// Exit code for an error that ended [`run`].
+fn exit_code(e: &SudokuError) -> i32 {
+    match e {
+        SudokuError::Solve(
+            SolveError::Unsatisfiable
+            | SolveError::Conflict(_)
+            | SolveError::Inconsistent { cell: Some(_) },
+        ) => EXIT_UNSATISFIABLE,
+        // Failing to decide a puzzle is not the puzzle's fault
+        SudokuError::Solve(
+            SolveError::Unknown | SolveError::Timeout | SolveError::Inconsistent { cell: None },
+        ) => EXIT_UNKNOWN,
+        SudokuError::Parse(_) | SudokuError::Io(_) => EXIT_INVALID_INPUT,
+    }
+}
+
+/// The exit code of the two that reports the worse failure: invalid input
+/// over an undecided puzzle over an unsolvable one, any of them over
+/// success.
+fn worse_exit_code(a: i32, b: i32) -> i32 {
+    let rank = |code| match code {
+        EXIT_INVALID_INPUT => 3,
+        EXIT_UNKNOWN => 2,
+        EXIT_SUCCESS => 0,
+        _ => 1,
+    };
+    if rank(b) > rank(a) {
+        b
+    } else {
+        a
+    }
+}
+
+/// Runs the mode selected by `opt` and returns the exit code, see the
+/// `EXIT_` constants. Errors end up with the code of [`exit_code`].
+fn run(opt: &Opt) -> Result<i32, SudokuError> {
+    if opt.generate {
+        let mut rng = match opt.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let sudoku: Sudoku = Sudoku::generate_with_symmetry(&mut rng, opt.symmetry);
+        match opt.format {
+            Format::Pretty => print!("{}", sudoku),
+            Format::Labeled => print!("{}", sudoku.to_labeled()),
+            Format::Compact => print!("{}", sudoku.to_compact()),
+            Format::Json => println!("{}", sudoku.to_json()),
+            Format::Svg => print!("{}", sudoku.to_svg()),
+            Format::Html => print!("{}", sudoku.to_html()),
+            Format::Latex => print!("{}", sudoku.to_latex()),
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+
+    let mut constraints = Constraints::default();
+    for variant in &opt.variants {
+        match variant {
+            Variant::X => constraints.diagonals = true,
+            Variant::Windoku => constraints.windoku = true,
+            Variant::AntiKnight => constraints.anti_knight = true,
+            Variant::AntiKing => constraints.anti_king = true,
+            Variant::Sandwich => {
+                constraints.row_sandwiches = opt.row_sums.0.clone();
+                constraints.col_sandwiches = opt.col_sums.0.clone();
+            }
+        }
+    }
+
+    if opt.batch {
+        return Ok(match &opt.file {
+            Some(path) => {
+                let file = std::fs::File::open(path)?;
+                solve_batch(std::io::BufReader::new(file), opt, &constraints)
+            }
+            None => solve_batch(std::io::stdin().lock(), opt, &constraints),
+        });
+    }
+
+    if opt.samurai {
+        return solve_samurai(opt);
+    }
+
+    if let [from, to] = opt.transcode[..] {
+        let grid = match &opt.file {
+            Some(path) => from.read(std::io::BufReader::new(std::fs::File::open(path)?)),
+            None => from.read(std::io::stdin().lock()),
+        }?;
+        print!("{}", to.write(&grid));
+        return Ok(EXIT_SUCCESS);
+    }
+
+    if opt.interactive {
+        let grid = match (&opt.puzzle, &opt.file) {
+            (None, None) => [[0u32; 9]; 9],
+            _ => read_grid_from_input(opt)?,
+        };
+        interactive(grid, &constraints, opt)?;
+        return Ok(EXIT_SUCCESS);
+    }
+
+    let grid = read_grid_from_input(opt)?;
+    if let Some(path) = &opt.verify {
+        let solution = read_grid_from_path(path, opt.input_format)?;
+        match verify(&grid, &solution) {
+            Ok(()) => println!("valid"),
+            Err(reason) => {
+                println!("invalid ({})", reason);
+                return Ok(EXIT_UNSATISFIABLE);
+            }
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+    let start = Instant::now();
+    let mut sudoku = Sudoku::with_constraints(grid, &constraints);
+    let encoding_time = start.elapsed();
+    if opt.dimacs {
+        print!("{}", sudoku.to_dimacs());
+        return Ok(EXIT_SUCCESS);
+    }
+    if opt.check {
+        let (verdict, code) = match sudoku.count_solutions(2) {
+            0 => ("unsolvable", EXIT_UNSATISFIABLE),
+            1 => ("unique", EXIT_SUCCESS),
+            _ => ("multiple", EXIT_MULTIPLE),
+        };
+        println!("{} ({} clues)", verdict, sudoku.clue_count());
+        warn_few_clues(&sudoku, opt);
+        return Ok(code);
+    }
+    if let Some(limit) = opt.count {
+        let limit = limit.unwrap_or(DEFAULT_COUNT_LIMIT);
+        let count = sudoku.count_solutions(limit);
+        if count == limit {
+            println!("{}+", count);
+        } else {
+            println!("{}", count);
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+    if let Some(limit) = opt.all {
+        let limit = limit.unwrap_or(DEFAULT_ALL_LIMIT);
+        let solutions = sudoku.solve_all(Some(limit));
+        if solutions.is_empty() {
+            return Err(SolveError::Unsatisfiable.into());
+        }
+        for (n, &solution) in solutions.iter().enumerate() {
+            if n > 0 {
+                println!();
+            }
+            sudoku.set_solution(solution)?;
+            match opt.format {
+                Format::Pretty => print!("{}", pretty(&sudoku, opt)),
+                Format::Labeled => print!("{}", sudoku.to_labeled()),
+                Format::Compact => print!("{}", sudoku.to_compact()),
+                Format::Json => println!("{}", sudoku.to_json()),
+                Format::Svg => print!("{}", sudoku.to_svg()),
+                Format::Html => print!("{}", sudoku.to_html()),
+                Format::Latex => print!("{}", sudoku.to_latex()),
+            }
+        }
+        if solutions.len() == limit {
+            eprintln!("Stopped after {} solutions, there may be more", limit);
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+    if opt.rate {
+        println!("{} ({} clues)", sudoku.difficulty()?, sudoku.clue_count());
+        warn_few_clues(&sudoku, opt);
+        return Ok(EXIT_SUCCESS);
+    }
+    if opt.canonical {
+        let canonical = Sudoku::new(sudoku.canonical());
+        println!("{}", canonical.to_oneline());
+        return Ok(EXIT_SUCCESS);
+    }
+    if opt.hint {
+        match sudoku.hint() {
+            Some(((i, j), value)) => println!("R{}C{} = {}", i + 1, j + 1, value),
+            None => {
+                eprintln!("No hint available, the puzzle is full or has no solution");
+                return Ok(EXIT_UNSATISFIABLE);
+            }
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+    if opt.explain {
+        let steps = sudoku.explain_steps();
+        for (n, step) in steps.iter().enumerate() {
+            println!("{}. {}", n + 1, step);
+        }
+        if sudoku.grid().iter().flatten().all(|&value| value != 0) {
+            println!("Solved in {} steps", steps.len());
+        } else {
+            println!("Stuck after {} steps, the rest needs search", steps.len());
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+    if opt.candidates {
+        print!("{}", sudoku.to_candidate_grid());
+        return Ok(EXIT_SUCCESS);
+    }
+    let input_text = text_grid(&sudoku, opt);
+    match &input_text {
+        Some(text) if !opt.side_by_side => println!("Input:\n{}", text),
+        _ => {}
+    }
+    let input = sudoku.to_json();
+    let result = if opt.timing {
+        eprintln!("Encoding: {:?}", encoding_time);
+        solve_with_timing(&mut sudoku, opt.timeout)
+    } else {
+        solve(&mut sudoku, opt.timeout)
+    };
+    if opt.stats {
+        let stats = sudoku.stats();
+        eprintln!("Variables: {}", stats.num_vars);
+        eprintln!("Clauses: {}", stats.num_clauses);
+        eprintln!("Learnt clauses: {}", stats.learnt_clauses);
+    }
+    if opt.format == Format::Json && !opt.diff {
+        let output = match result {
+            Ok(()) => sudoku.to_json(),
+            Err(_) => "null".to_string(),
+        };
+        println!(
+            "{{\"input\": {}, \"output\": {}, \"solved\": {}}}",
+            input,
+            output,
+            result.is_ok()
+        );
+    }
+    match (&input_text, &result) {
+        // Without a solution to put next to it, the input is printed alone
+        (Some(text), Err(_)) if opt.side_by_side => println!("Input:\n{}", text),
+        _ => {}
+    }
+    match result {
+        Ok(()) => {}
+        Err(SolveError::Unsatisfiable) => {
+            eprintln!("This puzzle has no solution");
+            let core = sudoku.explain_unsat();
+            if !core.is_empty() {
+                let cells = core
+                    .iter()
+                    .map(|((i, j), value)| format!("R{}C{} = {}", i + 1, j + 1, value))
+                    .collect::<Vec<_>>();
+                eprintln!(
+                    "Remove one of these givens to make the others consistent: {}",
+                    cells.join(", ")
+                );
             }
-            writeln!(f)?;
-            if i == 2 || i == 5 || i == 8 {
-                writeln!(f, "+-------+-------+-------+")?;
+            return Ok(EXIT_UNSATISFIABLE);
+        }
+        Err(SolveError::Conflict(conflict)) => {
+            eprintln!("This puzzle has no solution: {}", conflict);
+            return Ok(EXIT_UNSATISFIABLE);
+        }
+        Err(e) => return Err(e.into()),
+    }
+    if opt.diff {
+        for ((i, j), value) in sudoku.filled_cells() {
+            println!("R{}C{} = {}", i + 1, j + 1, value);
+        }
+        return Ok(EXIT_SUCCESS);
+    }
+    match (text_grid(&sudoku, opt), input_text) {
+        (Some(output), Some(input)) if opt.side_by_side => print!(
+            "{}",
+            side_by_side(
+                &format!("Input:\n{}", input),
+                &format!("Output:\n{}", output)
+            )
+        ),
+        (Some(output), _) => println!("Output:\n{}", output),
+        (None, _) => match opt.format {
+            Format::Svg => print!("{}", sudoku.to_svg()),
+            Format::Html => print!("{}", sudoku.to_html()),
+            Format::Latex => print!("{}", sudoku.to_latex()),
+            _ => {}
+        },
+    }
+    Ok(EXIT_SUCCESS)
+}
+
+/// Returns the grid in the text format chosen with `--format`, `None` for
+/// the formats that only print the solution or print both at once.
+fn text_grid(sudoku: &Sudoku, opt: &Opt) -> Option<String> {
+    match opt.format {
+        Format::Pretty => Some(pretty(sudoku, opt)),
+        Format::Labeled => Some(sudoku.to_labeled()),
+        Format::Compact => Some(sudoku.to_compact()),
+        Format::Json | Format::Svg | Format::Html | Format::Latex => None,
+    }
+}
+
+/// Spaces between the two grids of `--side-by-side`.
+const SIDE_BY_SIDE_GAP: usize = 4;
+
+/// Puts the lines of `left` and `right` next to each other, padding the
+/// lines of `left` to the same width.
+fn side_by_side(left: &str, right: &str) -> String {
+    let width = left.lines().map(visible_width).max().unwrap_or(0) + SIDE_BY_SIDE_GAP;
+    let (left, right) = (
+        left.lines().collect::<Vec<_>>(),
+        right.lines().collect::<Vec<_>>(),
+    );
+    let mut out = String::new();
+    for l in 0..left.len().max(right.len()) {
+        let (a, b) = (left.get(l).unwrap_or(&""), right.get(l).unwrap_or(&""));
+        let line = format!("{}{}{}", a, " ".repeat(width - visible_width(a)), b);
+        out += line.trim_end();
+        out.push('\n');
+    }
+    out
+}
+
+/// Number of characters `line` takes up on a terminal, not counting the
+/// ANSI color codes of the colored box drawing.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if !in_escape => width += 1,
+            _ => {}
+        }
+    }
+    width
+}
+
+/// Returns the box drawing of `sudoku`, colored unless disabled with
+/// `--no-color` or stdout isn't a terminal.
+fn pretty(sudoku: &Sudoku, opt: &Opt) -> String {
+    if !opt.no_color && std::io::stdout().is_terminal() {
+        sudoku.to_colored()
+    } else {
+        sudoku.to_string()
+    }
+}
+
+/// Checks that `solution` is a full valid grid keeping the givens of
+/// `puzzle`, returning the first problem found otherwise.
+fn verify(puzzle: &[[u32; 9]; 9], solution: &[[u32; 9]; 9]) -> Result<(), String> {
+    for (i, (puzzle_i, solution_i)) in puzzle.iter().zip(solution).enumerate() {
+        for (j, (&given, &value)) in puzzle_i.iter().zip(solution_i).enumerate() {
+            if value == 0 {
+                return Err(format!("R{}C{} is empty", i + 1, j + 1));
+            }
+            if given != 0 && given != value {
+                return Err(format!(
+                    "R{}C{} is {} but the puzzle gives {}",
+                    i + 1,
+                    j + 1,
+                    value,
+                    given
+                ));
             }
         }
-        Ok(())
     }
+    if !Sudoku::is_valid_solution(solution) {
+        // A full grid breaks the rules by repeating a value somewhere
+        return Err(match Sudoku::validate_givens(solution) {
+            Err(conflict) => format!(
+                "R{}C{} and R{}C{} both contain {}",
+                conflict.first.0 + 1,
+                conflict.first.1 + 1,
+                conflict.second.0 + 1,
+                conflict.second.1 + 1,
+                conflict.value
+            ),
+            Ok(()) => "the grid breaks the rules".to_string(),
+        });
+    }
+    Ok(())
 }
 
-impl Sudoku {
-    fn new(grid: [[u32; 9]; 9]) -> Self {
-        let mut solver = rsat::msat::Solver::new(rsat::msat::SolverOptions::default());
-        let mut lits = [[[Lit::new(0, false); 9]; 9]; 9];
+/// Commands understood by `--interactive`.
+const INTERACTIVE_HELP: &str = "\
+set RxCy v      give cell (x, y) the value v
+clear RxCy      empty cell (x, y)
+candidates RxCy print the values still possible in cell (x, y)
+solve           solve the puzzle and print the solution
+hint            print the value of one empty cell
+count           print the number of solutions
+show            print the grid
+help            print this list
+quit            leave";
 
-        for lits_i in &mut lits {
-            for lits_ij in lits_i.iter_mut().take(9) {
-                for lits_ijk in lits_ij.iter_mut().take(9) {
-                    // Cell (i, j) is assigned k+1
-                    *lits_ijk = Lit::new(solver.new_var(), false);
+/// Runs the `--interactive` loop on `grid` until stdin ends or `quit`.
+///
+/// Every given, the ones from the input included, is set with
+/// [`Sudoku::set_given`], so the solver is built once and any of them can
+/// be changed or cleared later.
+fn interactive(
+    grid: [[u32; 9]; 9],
+    constraints: &Constraints,
+    opt: &Opt,
+) -> Result<(), SudokuError> {
+    let mut sudoku = Sudoku::with_constraints([[0u32; 9]; 9], constraints);
+    for (i, row) in grid.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            sudoku.set_given(i, j, value);
+        }
+    }
+    let prompt = std::io::stdin().is_terminal();
+    println!("{}", pretty(&sudoku, opt));
+    let mut lines = std::io::stdin().lock().lines();
+    loop {
+        if prompt {
+            print!("> ");
+            std::io::Write::flush(&mut std::io::stdout())?;
+        }
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => return Ok(()),
+        };
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        match words.as_slice() {
+            [] => {}
+            ["quit" | "exit"] => return Ok(()),
+            ["help"] => println!("{}", INTERACTIVE_HELP),
+            ["show"] => println!("{}", pretty(&sudoku, opt)),
+            ["set", cell, value] => match (parse_cell(cell), value.parse::<u32>()) {
+                (Some((i, j)), Ok(value @ 1..=9)) => {
+                    sudoku.set_given(i, j, value);
+                    println!("{}", pretty(&sudoku, opt));
+                }
+                (None, _) => eprintln!("Invalid cell {:?}, expected e.g. R4C5", cell),
+                (_, _) => eprintln!("Invalid value {:?}, expected 1-9", value),
+            },
+            ["clear", cell] => match parse_cell(cell) {
+                Some((i, j)) => {
+                    sudoku.set_given(i, j, 0);
+                    println!("{}", pretty(&sudoku, opt));
+                }
+                None => eprintln!("Invalid cell {:?}, expected e.g. R4C5", cell),
+            },
+            ["candidates", cell] => match parse_cell(cell) {
+                Some((i, j)) => {
+                    let values = sudoku.candidates()[i][j]
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect::<Vec<_>>();
+                    println!("R{}C{}: {}", i + 1, j + 1, values.join(" "));
+                }
+                None => eprintln!("Invalid cell {:?}, expected e.g. R4C5", cell),
+            },
+            ["solve"] => match sudoku.solve() {
+                Ok(()) => println!("{}", pretty(&sudoku, opt)),
+                Err(e) => eprintln!("Error: {}", e),
+            },
+            ["hint"] => match sudoku.hint() {
+                Some(((i, j), value)) => println!("R{}C{} = {}", i + 1, j + 1, value),
+                None => eprintln!("No hint available, the puzzle is full or has no solution"),
+            },
+            ["count"] => {
+                let count = sudoku.count_solutions(DEFAULT_COUNT_LIMIT);
+                if count == DEFAULT_COUNT_LIMIT {
+                    println!("{}+", count);
+                } else {
+                    println!("{}", count);
                 }
             }
+            _ => eprintln!("Unknown command {:?}, try help", line.trim()),
         }
+    }
+}
 
-        // Exactly one value is assigned to each cell
-        // Each horizontal line contains k exactly once
-        // Each vertical line contains k exactly once
-        // Each 3x3 grid contains k exactly once
-        for i in 0..9 {
-            for j in 0..9 {
-                let mut cl = vec![];
-                for k in 0..9 {
-                    cl.push(lits[i][j][k]);
-                    for l in 0..9 {
-                        if k != l {
-                            // Cell(i, j) == k+1 => Cell(i, j) != l+1 for k != l
-                            solver.new_clause(vec![!lits[i][j][k], !lits[i][j][l]]);
-                        }
-                        if j != l {
-                            // Cell(i, j) == k+1 => Cell(i, l) != k+1 for j != l
-                            solver.new_clause(vec![!lits[i][j][k], !lits[i][l][k]]);
-                        }
-                        if i != l {
-                            // Cell(i, j) == k+1 => Cell(l, j) != k+1 for i != l
-                            solver.new_clause(vec![!lits[i][j][k], !lits[l][j][k]]);
-                        }
-
-                        let mod_i = (i / 3) * 3 + l / 3;
-                        let mod_j = (j / 3) * 3 + l % 3;
-                        if i != mod_i || j != mod_j {
-                            // Cell(i, j) == k+1 => Cell(mod_i, mod_j) != k+1 for i != mod_i, j != mod_j
-                            solver.new_clause(vec![!lits[i][j][k], !lits[mod_i][mod_j][k]]);
-                        }
-                    }
-                }
+/// Parses a cell written as `R4C5`, 1-based, into 0-based coordinates.
+fn parse_cell(s: &str) -> Option<(usize, usize)> {
+    let s = s.to_ascii_uppercase();
+    let (row, col) = s.strip_prefix('R')?.split_once('C')?;
+    let (i, j) = (row.parse::<usize>().ok()?, col.parse::<usize>().ok()?);
+    if (1..=9).contains(&i) && (1..=9).contains(&j) {
+        Some((i - 1, j - 1))
+    } else {
+        None
+    }
+}
+
+/// Warns on stderr if the puzzle has too few clues for a unique solution,
+/// which variants can overcome.
+fn warn_few_clues(sudoku: &Sudoku, opt: &Opt) {
+    let clues = sudoku.clue_count();
+    if clues < Sudoku::MIN_UNIQUE_CLUES && opt.variants.is_empty() {
+        eprintln!(
+            "Warning: {} clues, a puzzle needs at least {} to have a unique solution",
+            clues,
+            Sudoku::MIN_UNIQUE_CLUES
+        );
+    }
+}
+
+/// Solutions counted by `--count` without a limit.
+const DEFAULT_COUNT_LIMIT: usize = 1000;
 
-                // At least one of 1..=9 is assigned to Cell(i, j)
-                solver.new_clause(cl);
+/// Solutions printed by `--all` without a limit.
+const DEFAULT_ALL_LIMIT: usize = 100;
 
-                if grid[i][j] != 0 {
-                    // Unit clause for already assigned cells
-                    solver.new_clause(vec![lits[i][j][grid[i][j] as usize - 1]]);
+/// Puzzles read ahead per thread in `--batch` mode with more than one job.
+const PUZZLES_PER_JOB: usize = 64;
+
+/// A puzzle read in `--batch` mode.
+enum BatchPuzzle {
+    /// A single line of 81 cells, solved to a single line.
+    Line(String),
+    /// Nine lines of nine cells, solved to nine lines.
+    Grid(String),
+}
+
+/// Solves every puzzle read from `reader`, printing the solution or
+/// `UNSOLVED` for each of them. Returns the exit code of the worst failure,
+/// see [`worse_exit_code`], or [`EXIT_SUCCESS`] if all puzzles were solved.
+///
+/// A line of 81 cells is a puzzle of its own. Other lines are collected
+/// into a nine-line grid up to the next blank line or the end of the input,
+/// and the solved grids are printed separated by blank lines. Blank lines
+/// and lines starting with `#` are skipped otherwise.
+///
+/// The input is streamed: with one job each puzzle is solved and printed
+/// before the next line is read. With more jobs up to
+/// [`PUZZLES_PER_JOB`] puzzles per job are read, solved on a thread pool
+/// and printed in input order before reading on. Memory use doesn't grow
+/// with the size of the input either way. The rules are encoded once in a
+/// [`SudokuTemplate`], with more jobs a few times per batch of puzzles.
+fn solve_batch<R: BufRead>(reader: R, opt: &Opt, constraints: &Constraints) -> i32 {
+    let pool = if opt.jobs > 1 {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(opt.jobs)
+            .build()
+            .expect("couldn't start the thread pool");
+        Some(pool)
+    } else {
+        None
+    };
+    let chunk = if pool.is_some() {
+        PUZZLES_PER_JOB * opt.jobs
+    } else {
+        1
+    };
+    let mut puzzles = Vec::with_capacity(chunk);
+    let mut template = SudokuTemplate::with_constraints(constraints);
+    let mut code = EXIT_SUCCESS;
+    let mut output = BatchOutput::default();
+    // Lines of the grid being read and the line it started on
+    let mut grid = String::new();
+    let mut grid_start = 0;
+    let mut lines = reader.lines().enumerate();
+    loop {
+        let (n, line) = match lines.next() {
+            Some((n, Ok(line))) => (n + 1, Some(line)),
+            Some((_, Err(e))) => {
+                eprintln!("Error: {}", e);
+                code = worse_exit_code(code, EXIT_INVALID_INPUT);
+                (0, None)
+            }
+            None => (0, None),
+        };
+        let raw = line.as_deref();
+        let puzzle = match raw.map(str::trim) {
+            Some(line) if line.starts_with('#') => continue,
+            Some(line) if !line.is_empty() && grid.is_empty() && line.chars().count() == 81 => {
+                Some((n, BatchPuzzle::Line(line.to_string())))
+            }
+            Some(line) if !line.is_empty() => {
+                if grid.is_empty() {
+                    grid_start = n;
                 }
+                // Untrimmed, whitespace can be an empty cell
+                grid.push_str(raw.unwrap_or_default());
+                grid.push('\n');
+                continue;
             }
+            // A blank line or the end of the input finishes a grid
+            _ if !grid.is_empty() => {
+                Some((grid_start, BatchPuzzle::Grid(std::mem::take(&mut grid))))
+            }
+            Some(_) => continue,
+            None => None,
+        };
+        if let Some(puzzle) = puzzle {
+            puzzles.push(puzzle);
+        }
+        if puzzles.len() >= chunk || (raw.is_none() && !puzzles.is_empty()) {
+            let solutions = match &pool {
+                Some(pool) => pool.install(|| {
+                    puzzles
+                        .par_iter()
+                        .map_init(
+                            || SudokuTemplate::with_constraints(constraints),
+                            |template, (_, puzzle)| solve_puzzle(puzzle, template, opt.timeout),
+                        )
+                        .collect::<Vec<_>>()
+                }),
+                None => puzzles
+                    .iter()
+                    .map(|(_, puzzle)| solve_puzzle(puzzle, &mut template, opt.timeout))
+                    .collect(),
+            };
+            for ((n, puzzle), solution) in puzzles.drain(..).zip(solutions) {
+                code = worse_exit_code(code, output.print(n, &puzzle, solution));
+            }
+        }
+        if raw.is_none() {
+            break;
         }
+    }
+    code
+}
 
-        Sudoku { grid, solver }
+/// Solves the Samurai Sudoku read from the input and prints the solved
+/// 21x21 square. Variants, output formats and the timeout don't apply.
+fn solve_samurai(opt: &Opt) -> Result<i32, SudokuError> {
+    let grids = match &opt.file {
+        Some(path) => read_samurai(std::io::BufReader::new(std::fs::File::open(path)?)),
+        None => read_samurai(std::io::stdin().lock()),
+    }?;
+    let mut samurai = Samurai::new(grids);
+    match samurai.solve() {
+        Ok(()) => print!("{}", samurai),
+        Err(SolveError::Unsatisfiable) => {
+            eprintln!("This puzzle has no solution");
+            return Ok(EXIT_UNSATISFIABLE);
+        }
+        Err(e) => return Err(e.into()),
     }
+    Ok(EXIT_SUCCESS)
+}
 
-    fn solve(&mut self) {
-        match self.solver.solve(vec![]) {
-            Solution::Sat(sol) => {
-                for i in 0..9 {
-                    for j in 0..9 {
-                        for k in 0..9 {
-                            if sol[9 * 9 * i + 9 * j + k] {
-                                if self.grid[i][j] != 0 && self.grid[i][j] != k as u32 + 1 {
-                                    panic!("Something wrong, couldn't solve!");
-                                }
-                                self.grid[i][j] = k as u32 + 1;
-                            }
-                        }
-                    }
-                }
+/// Solves a puzzle of `--batch` mode with the rules encoded in `template`.
+fn solve_puzzle(
+    puzzle: &BatchPuzzle,
+    template: &mut SudokuTemplate,
+    timeout: Option<u64>,
+) -> Result<String, SudokuError> {
+    let grid = match puzzle {
+        BatchPuzzle::Line(line) => parse_oneline(line),
+        BatchPuzzle::Grid(lines) => read_grid(lines.as_bytes()),
+    }?;
+    let sudoku = match timeout {
+        Some(ms) => template.solve_with_timeout(grid, Duration::from_millis(ms)),
+        None => template.solve(grid),
+    }?;
+    match puzzle {
+        BatchPuzzle::Line(_) => Ok(sudoku.to_oneline()),
+        BatchPuzzle::Grid(_) => Ok(sudoku.to_compact().trim_end().to_string()),
+    }
+}
+
+/// Solves `sudoku`, within `timeout` milliseconds if one is given.
+fn solve(sudoku: &mut Sudoku, timeout: Option<u64>) -> Result<(), SolveError> {
+    match timeout {
+        Some(ms) => sudoku.solve_with_timeout(Duration::from_millis(ms)),
+        None => sudoku.solve(),
+    }
+}
+
+/// Like [`solve`], printing the time spent to stderr. The search with a
+/// timeout runs on another thread, so its decoding isn't timed apart.
+fn solve_with_timing(sudoku: &mut Sudoku, timeout: Option<u64>) -> Result<(), SolveError> {
+    if timeout.is_some() {
+        let start = Instant::now();
+        let result = solve(sudoku, timeout);
+        eprintln!("Search and decoding: {:?}", start.elapsed());
+        return result;
+    }
+    let timing = sudoku.solve_timed()?;
+    eprintln!("Search: {:?}", timing.search);
+    eprintln!("Decoding: {:?}", timing.decoding);
+    Ok(())
+}
+
+/// Prints the solutions of `--batch` mode.
+#[derive(Default)]
+struct BatchOutput {
+    /// Whether a grid was printed, the next one is separated by a blank
+    /// line.
+    grid_printed: bool,
+}
+
+impl BatchOutput {
+    /// Prints the solution of the puzzle starting on line `n`, returns
+    /// the exit code for it.
+    fn print(
+        &mut self,
+        n: usize,
+        puzzle: &BatchPuzzle,
+        solution: Result<String, SudokuError>,
+    ) -> i32 {
+        if let BatchPuzzle::Grid(_) = puzzle {
+            if self.grid_printed {
+                println!();
+            }
+            self.grid_printed = true;
+        }
+        match solution {
+            Ok(solution) => {
+                println!("{}", solution);
+                EXIT_SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Line {}: {}", n, e);
+                println!("UNSOLVED");
+                exit_code(&e)
             }
-            Solution::Unsat | Solution::Unknown | Solution::Best(_) => panic!("Couldn't solve!"),
         }
     }
 }
 
-fn main() {
-    let mut sudoku = Sudoku::new(read_grid_from_stdin().unwrap());
-    println!("Input:\n{}", sudoku);
-    sudoku.solve();
-    println!("Output:\n{}", sudoku);
-}
-
-fn read_grid_from_stdin() -> Option<[[u32; 9]; 9]> {
-    let mut grid = [[0u32; 9]; 9];
-    for grid_i in &mut grid {
-        let mut line = String::new();
-        std::io::stdin().read_line(&mut line).unwrap();
-        if line.len() < 9 {
-            return None;
-        }
-        for (j, grid_ij) in grid_i.iter_mut().enumerate().take(9) {
-            *grid_ij = match line.chars().collect::<Vec<char>>()[j] {
-                c @ '1'..='9' => (c as u32 - '0' as u32),
-                _ => 0,
-            };
+/// Reads the puzzle from the command line argument, a single line unless
+/// it's JSON, `--file` or stdin.
+fn read_grid_from_input(opt: &Opt) -> Result<[[u32; 9]; 9], ParseGridError> {
+    match (&opt.puzzle, &opt.file) {
+        (Some(puzzle), _) => match opt.input_format {
+            InputFormat::Auto => parse_oneline(puzzle.trim()),
+            InputFormat::Json => parse_json(puzzle),
+        },
+        (None, Some(path)) => read_grid_from_path(path, opt.input_format),
+        (None, None) => read_grid_from_stdin(opt.input_format),
+    }
+}
+
+fn read_grid_from_stdin(format: InputFormat) -> Result<[[u32; 9]; 9], ParseGridError> {
+    let stdin = std::io::stdin();
+    parse_input(stdin.lock(), format)
+}
+
+fn read_grid_from_path(path: &Path, format: InputFormat) -> Result<[[u32; 9]; 9], ParseGridError> {
+    let file = std::fs::File::open(path)?;
+    parse_input(std::io::BufReader::new(file), format)
+}
+
+fn parse_input<R: BufRead>(
+    mut reader: R,
+    format: InputFormat,
+) -> Result<[[u32; 9]; 9], ParseGridError> {
+    match format {
+        InputFormat::Auto => read_grid(reader),
+        InputFormat::Json => {
+            let mut s = String::new();
+            reader.read_to_string(&mut s)?;
+            parse_json(&s)
         }
     }
-    Some(grid)
 }
@@ -0,0 +1,389 @@
+use crate::Symbols;
+use std::io::BufRead;
+
+/// Errors produced while reading a puzzle.
+#[derive(Debug, thiserror::Error)]
+pub enum ParseGridError {
+    /// The input ended before all rows were read, `got` of them were.
+    #[error("{}", too_few_lines(*.got))]
+    TooFewLines { got: usize },
+    /// A row has fewer cells than the board is wide, `line` is its 1-based
+    /// line in the input, counting blank, comment and border lines.
+    #[error("line {line} has fewer cells than a row")]
+    LineTooShort { line: usize },
+    /// A one-line puzzle doesn't have exactly one character per cell.
+    #[error("expected {expected} characters, found {len}")]
+    WrongLength { expected: usize, len: usize },
+    /// A character that is neither a value of the board nor a blank marker,
+    /// at 1-based `line` and `col` of the input.
+    #[error("invalid character {ch:?} at line {line}, column {col}")]
+    InvalidChar { line: usize, col: usize, ch: char },
+    /// JSON input that isn't an array of nine arrays of nine numbers.
+    #[error("invalid JSON grid: {0}")]
+    InvalidJson(String),
+    /// A JSON cell value above 9, `row` and `col` are 1-based.
+    #[error("value {value} at row {row}, column {col} is out of range 0-9")]
+    ValueOutOfRange { row: usize, col: usize, value: u64 },
+    /// Reading the input failed.
+    #[error("couldn't read input: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Message of [`ParseGridError::TooFewLines`].
+fn too_few_lines(got: usize) -> String {
+    match got {
+        0 => "the input is empty".to_string(),
+        1 => "the input ended after the first row".to_string(),
+        _ => format!("the input ended after {} rows", got),
+    }
+}
+
+/// Reads a puzzle either as nine lines of nine cells or as a single line of
+/// 81 cells. The box drawing printed by the `Display` implementation of
+/// [`crate::Sudoku`] is accepted as well.
+///
+/// Everything after a `#` on a line is a comment. Lines holding only a
+/// comment are skipped, and a row with cells and a comment, like
+/// `53..7....  # row 1`, may have whitespace around its cells. Any other
+/// character left after the cells of a row is an error.
+pub fn read_grid<R: BufRead>(reader: R) -> Result<[[u32; 9]; 9], ParseGridError> {
+    read_grid_with(reader, Symbols::default())
+}
+
+/// Like [`read_grid`] for a board of side `N`, with the values written in
+/// `symbols`: `N` lines of `N` cells, a single line of `N * N` cells or a
+/// box drawing.
+pub fn read_grid_with<const N: usize, R: BufRead>(
+    reader: R,
+    symbols: Symbols,
+) -> Result<[[u32; N]; N], ParseGridError> {
+    // Each line with its 1-based number, which errors report
+    let mut lines = reader.lines().zip(1..);
+
+    // Skip leading blank lines, the first non-empty line decides the format
+    let (first, first_n) = loop {
+        match lines.next() {
+            Some((line, n)) => {
+                let line = line?;
+                if !strip_comment(&line).trim().is_empty() {
+                    break (line, n);
+                }
+            }
+            None => return Err(ParseGridError::TooFewLines { got: 0 }),
+        }
+    };
+    if strip_comment(&first).trim_end().chars().count() == N * N {
+        return parse_oneline_at(&first, first_n, symbols);
+    }
+    if first.trim_start().starts_with('+') {
+        return parse_boxed(lines, symbols);
+    }
+
+    let mut grid = [[0u32; N]; N];
+    grid[0] = parse_row(&first, first_n, symbols)?;
+    let mut i = 1;
+    while i < N {
+        let (line, n) = match lines.next() {
+            Some((line, n)) => (line?, n),
+            None => return Err(ParseGridError::TooFewLines { got: i }),
+        };
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        grid[i] = parse_row(&line, n, symbols)?;
+        i += 1;
+    }
+    Ok(grid)
+}
+
+/// The part of `line` before a `#` comment.
+fn strip_comment(line: &str) -> &str {
+    match line.find('#') {
+        Some(end) => &line[..end],
+        None => line,
+    }
+}
+
+/// Parses a puzzle given as a single line of 81 characters in row-major
+/// order, optionally followed by a `#` comment.
+pub fn parse_oneline(s: &str) -> Result<[[u32; 9]; 9], ParseGridError> {
+    parse_oneline_with(s, Symbols::default())
+}
+
+/// Like [`parse_oneline`] for a board of side `N`, with the values written
+/// in `symbols`.
+pub fn parse_oneline_with<const N: usize>(
+    s: &str,
+    symbols: Symbols,
+) -> Result<[[u32; N]; N], ParseGridError> {
+    parse_oneline_at(s, 1, symbols)
+}
+
+/// [`parse_oneline_with`] for a puzzle found on input line `line`.
+fn parse_oneline_at<const N: usize>(
+    s: &str,
+    line: usize,
+    symbols: Symbols,
+) -> Result<[[u32; N]; N], ParseGridError> {
+    let chars = strip_comment(s).trim_end().chars().collect::<Vec<char>>();
+    if chars.len() != N * N {
+        return Err(ParseGridError::WrongLength {
+            expected: N * N,
+            len: chars.len(),
+        });
+    }
+    let mut grid = [[0u32; N]; N];
+    for (i, grid_i) in grid.iter_mut().enumerate() {
+        for (j, grid_ij) in grid_i.iter_mut().enumerate() {
+            *grid_ij = cell_value::<N>(chars[N * i + j], symbols, line, N * i + j + 1)?;
+        }
+    }
+    Ok(grid)
+}
+
+/// Parses a puzzle given as a JSON array of nine rows, each an array of nine
+/// numbers in `0..=9` with `0` for empty cells.
+pub fn parse_json(s: &str) -> Result<[[u32; 9]; 9], ParseGridError> {
+    let mut parser = JsonParser {
+        chars: s.char_indices().peekable(),
+    };
+    let rows = parser.parse_grid()?;
+    if rows.len() != 9 {
+        return Err(ParseGridError::InvalidJson(format!(
+            "expected 9 rows, found {}",
+            rows.len()
+        )));
+    }
+    let mut grid = [[0u32; 9]; 9];
+    for (i, (grid_i, row)) in grid.iter_mut().zip(&rows).enumerate() {
+        if row.len() != 9 {
+            return Err(ParseGridError::InvalidJson(format!(
+                "row {} has {} entries, expected 9",
+                i + 1,
+                row.len()
+            )));
+        }
+        for (j, (grid_ij, &value)) in grid_i.iter_mut().zip(row).enumerate() {
+            if value > 9 {
+                return Err(ParseGridError::ValueOutOfRange {
+                    row: i + 1,
+                    col: j + 1,
+                    value,
+                });
+            }
+            *grid_ij = value as u32;
+        }
+    }
+    Ok(grid)
+}
+
+/// Writes `grid` as `N` lines of `N` cells without borders, the values in
+/// `symbols` and `.` for empty cells. This is one of the formats
+/// [`read_grid_with`] reads.
+///
+/// # Panics
+///
+/// Panics if a value is above [`Symbols::max_value`] of `symbols`.
+pub fn format_compact<const N: usize>(grid: &[[u32; N]; N], symbols: Symbols) -> String {
+    let mut out = String::new();
+    for row in grid {
+        out.extend(row.iter().map(|&value| symbols.symbol_for(value)));
+        out.push('\n');
+    }
+    out
+}
+
+/// Writes `grid` as a single line of `N * N` cells in row-major order, the
+/// format [`parse_oneline_with`] reads.
+///
+/// # Panics
+///
+/// Panics if a value is above [`Symbols::max_value`] of `symbols`.
+pub fn format_oneline<const N: usize>(grid: &[[u32; N]; N], symbols: Symbols) -> String {
+    grid.iter()
+        .flatten()
+        .map(|&value| symbols.symbol_for(value))
+        .collect()
+}
+
+/// Writes `grid` as a JSON array of `N` rows of `N` numbers, `0` for empty
+/// cells, the format [`parse_json`] reads.
+pub fn format_json<const N: usize>(grid: &[[u32; N]; N]) -> String {
+    let rows = grid
+        .iter()
+        .map(|row| {
+            let cells = row.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            format!("[{}]", cells.join(","))
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", rows.join(","))
+}
+
+/// Just enough of a JSON parser to read nested arrays of unsigned integers.
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::CharIndices<'a>>,
+}
+
+impl JsonParser<'_> {
+    fn parse_grid(&mut self) -> Result<Vec<Vec<u64>>, ParseGridError> {
+        let rows = self.parse_array(|p| p.parse_array(JsonParser::parse_number))?;
+        self.skip_whitespace();
+        match self.chars.next() {
+            None => Ok(rows),
+            Some((pos, c)) => Err(Self::unexpected(pos, c)),
+        }
+    }
+
+    fn parse_array<T>(
+        &mut self,
+        mut parse_item: impl FnMut(&mut Self) -> Result<T, ParseGridError>,
+    ) -> Result<Vec<T>, ParseGridError> {
+        self.expect('[')?;
+        let mut items = vec![];
+        self.skip_whitespace();
+        if let Some(&(_, ']')) = self.chars.peek() {
+            self.chars.next();
+            return Ok(items);
+        }
+        loop {
+            items.push(parse_item(self)?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ',')) => {}
+                Some((_, ']')) => return Ok(items),
+                Some((pos, c)) => return Err(Self::unexpected(pos, c)),
+                None => return Err(Self::end_of_input()),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<u64, ParseGridError> {
+        self.skip_whitespace();
+        let mut value: Option<u64> = None;
+        while let Some(&(_, c @ '0'..='9')) = self.chars.peek() {
+            self.chars.next();
+            let digit = u64::from(c as u32 - '0' as u32);
+            // Saturate so a huge number is reported as out of range
+            value = Some(value.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+        }
+        match (value, self.chars.peek()) {
+            (Some(value), _) => Ok(value),
+            (None, Some(&(pos, c))) => Err(Self::unexpected(pos, c)),
+            (None, None) => Err(Self::end_of_input()),
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), ParseGridError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some((_, c)) if c == expected => Ok(()),
+            Some((pos, c)) => Err(Self::unexpected(pos, c)),
+            None => Err(Self::end_of_input()),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn unexpected(pos: usize, c: char) -> ParseGridError {
+        ParseGridError::InvalidJson(format!("unexpected {:?} at offset {}", c, pos))
+    }
+
+    fn end_of_input() -> ParseGridError {
+        ParseGridError::InvalidJson("unexpected end of input".to_string())
+    }
+}
+
+/// Parses a row from the `N` characters of `line`, input line `n`, once a
+/// comment is removed. Whitespace around the cells is dropped if there is
+/// more than a row's worth of characters, otherwise it stands for empty
+/// cells.
+fn parse_row<const N: usize>(
+    line: &str,
+    n: usize,
+    symbols: Symbols,
+) -> Result<[u32; N], ParseGridError> {
+    let cells = strip_comment(line).chars().zip(1..).collect::<Vec<_>>();
+    let cells = if cells.len() > N {
+        let start = cells.iter().position(|(c, _)| !c.is_whitespace());
+        let start = start.unwrap_or(cells.len());
+        let end = cells.iter().rposition(|(c, _)| !c.is_whitespace());
+        &cells[start..end.map_or(start, |end| end + 1)]
+    } else {
+        &cells[..]
+    };
+    parse_cells(cells, n, symbols)
+}
+
+/// Parses a row from `cells`, each a character with its 1-based column on
+/// input line `n`. A cell past the `N`th is reported as an invalid
+/// character.
+fn parse_cells<const N: usize>(
+    cells: &[(char, usize)],
+    n: usize,
+    symbols: Symbols,
+) -> Result<[u32; N], ParseGridError> {
+    if cells.len() < N {
+        return Err(ParseGridError::LineTooShort { line: n });
+    }
+    if let Some(&(ch, col)) = cells.get(N) {
+        return Err(ParseGridError::InvalidChar { line: n, col, ch });
+    }
+    let mut row = [0u32; N];
+    for (row_j, &(c, col)) in row.iter_mut().zip(cells) {
+        *row_j = cell_value::<N>(c, symbols, n, col)?;
+    }
+    Ok(row)
+}
+
+/// Parses the rows of a box drawing once its top border has been read,
+/// `lines` are numbered from 1 like the input.
+/// Border lines are skipped, `|` and whitespace separate the cells.
+/// Comments are removed from the rows, lines holding only a comment are
+/// skipped.
+fn parse_boxed<const N: usize, I>(
+    mut lines: I,
+    symbols: Symbols,
+) -> Result<[[u32; N]; N], ParseGridError>
+where
+    I: Iterator<Item = (std::io::Result<String>, usize)>,
+{
+    let mut grid = [[0u32; N]; N];
+    let mut i = 0;
+    while i < N {
+        let (line, n) = match lines.next() {
+            Some((line, n)) => (line?, n),
+            None => return Err(ParseGridError::TooFewLines { got: i }),
+        };
+        if line.trim_start().starts_with(['+', '#']) {
+            continue;
+        }
+        let cells = strip_comment(&line)
+            .chars()
+            .zip(1..)
+            .filter(|&(c, _)| c != '|' && !c.is_whitespace())
+            .collect::<Vec<_>>();
+        grid[i] = parse_cells(&cells, n, symbols)?;
+        i += 1;
+    }
+    Ok(grid)
+}
+
+/// Value of a cell on a board of side `N`, see [`Symbols::value_for`].
+/// `line` and `col` (1-based) are only used for error reporting.
+pub(crate) fn cell_value<const N: usize>(
+    c: char,
+    symbols: Symbols,
+    line: usize,
+    col: usize,
+) -> Result<u32, ParseGridError> {
+    symbols
+        .value_for(c, N)
+        .ok_or(ParseGridError::InvalidChar { line, col, ch: c })
+}
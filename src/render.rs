@@ -0,0 +1,291 @@
+use crate::{BoxShape, Sudoku};
+
+/// Side of a cell in the SVG drawing, in pixels.
+const CELL: usize = 40;
+/// Space around the grid so the outer border isn't cut off.
+const MARGIN: usize = 2;
+
+impl<const N: usize> Sudoku<N> {
+    /// Draws the current grid as an SVG image.
+    ///
+    /// Box and region boundaries get thick lines and the other cell
+    /// boundaries thin ones. Givens are drawn in bold, the cells filled in by the solver in
+    /// regular weight.
+    pub fn to_svg(&self) -> String {
+        let shape = self.ruled_shape();
+        let size = N * CELL + 2 * MARGIN;
+        let mut out = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n",
+            size
+        );
+        out += &format!(
+            "<rect width=\"{0}\" height=\"{0}\" fill=\"white\"/>\n",
+            size
+        );
+
+        for l in 0..=N {
+            let pos = MARGIN + l * CELL;
+            let (start, end) = (MARGIN, MARGIN + N * CELL);
+            let width = if l % shape.cols == 0 { 3 } else { 1 };
+            out += &format!(
+                "<line x1=\"{0}\" y1=\"{1}\" x2=\"{0}\" y2=\"{2}\" stroke=\"black\" stroke-width=\"{3}\"/>\n",
+                pos, start, end, width
+            );
+            let width = if l % shape.rows == 0 { 3 } else { 1 };
+            out += &format!(
+                "<line x1=\"{1}\" y1=\"{0}\" x2=\"{2}\" y2=\"{0}\" stroke=\"black\" stroke-width=\"{3}\"/>\n",
+                pos, start, end, width
+            );
+        }
+
+        if self.box_shape.is_none() {
+            // The region edges, one cell side at a time
+            for i in 0..N {
+                for j in 0..N {
+                    let (x, y) = (MARGIN + j * CELL, MARGIN + i * CELL);
+                    let mut edges = vec![];
+                    if j + 1 < N && self.regions[i][j] != self.regions[i][j + 1] {
+                        edges.push((x + CELL, y, x + CELL, y + CELL));
+                    }
+                    if i + 1 < N && self.regions[i][j] != self.regions[i + 1][j] {
+                        edges.push((x, y + CELL, x + CELL, y + CELL));
+                    }
+                    for (x1, y1, x2, y2) in edges {
+                        out += &format!(
+                            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\" stroke-width=\"3\" stroke-linecap=\"square\"/>\n",
+                            x1, y1, x2, y2
+                        );
+                    }
+                }
+            }
+        }
+
+        for (i, row) in self.grid.iter().enumerate() {
+            for (j, &value) in row.iter().enumerate() {
+                if value == 0 {
+                    continue;
+                }
+                let weight = if self.is_given(i, j) {
+                    "bold"
+                } else {
+                    "normal"
+                };
+                out += &format!(
+                    "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" font-weight=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                    MARGIN + j * CELL + CELL / 2,
+                    MARGIN + i * CELL + CELL / 2,
+                    CELL * 3 / 5,
+                    weight,
+                    self.symbols.symbol_for(value)
+                );
+            }
+        }
+        out += "</svg>\n";
+        out
+    }
+
+    /// Writes the current grid as an HTML `<table class="sudoku">`, one row
+    /// per grid row, without any styling.
+    ///
+    /// Givens have `class="given"` and cells filled in by the solver
+    /// `class="solved"`, empty cells are left blank. Cells on the right or
+    /// bottom edge of a box or region, except along the outer border, also
+    /// get `box-right` or `box-bottom` for thicker borders.
+    pub fn to_html(&self) -> String {
+        let regions = &self.regions;
+        let mut out = "<table class=\"sudoku\">\n".to_string();
+        for (i, row) in self.grid.iter().enumerate() {
+            out += "<tr>";
+            for (j, &value) in row.iter().enumerate() {
+                let mut classes = vec![];
+                if value != 0 {
+                    classes.push(if self.is_given(i, j) {
+                        "given"
+                    } else {
+                        "solved"
+                    });
+                }
+                if j + 1 < N && regions[i][j] != regions[i][j + 1] {
+                    classes.push("box-right");
+                }
+                if i + 1 < N && regions[i][j] != regions[i + 1][j] {
+                    classes.push("box-bottom");
+                }
+                if classes.is_empty() {
+                    out += "<td>";
+                } else {
+                    out += &format!("<td class=\"{}\">", classes.join(" "));
+                }
+                if value != 0 {
+                    out.push(self.symbols.symbol_for(value));
+                }
+                out += "</td>";
+            }
+            out += "</tr>\n";
+        }
+        out += "</table>\n";
+        out
+    }
+
+    /// Writes the current grid as a LaTeX `tabular` with thick rules
+    /// around the boxes, ready to be `\input` into a document that loads
+    /// the `array` package. Givens are set in bold, empty cells are blank.
+    ///
+    /// The regions of a Jigsaw Sudoku don't follow whole rows and columns
+    /// of the table, so only its outer border is thick.
+    pub fn to_latex(&self) -> String {
+        let shape = self.ruled_shape();
+        let thick = "!{\\vrule width 1.2pt}";
+        let mut columns = thick.to_string();
+        for j in 0..N {
+            columns.push('c');
+            columns += if j % shape.cols == shape.cols - 1 {
+                thick
+            } else {
+                "|"
+            };
+        }
+
+        let mut out = "{\\renewcommand{\\arraystretch}{1.5}\n".to_string();
+        out += &format!("\\begin{{tabular}}{{{}}}\n", columns);
+        out += "\\noalign{\\hrule height 1.2pt}\n";
+        for (i, row) in self.grid.iter().enumerate() {
+            let cells = row
+                .iter()
+                .enumerate()
+                .map(|(j, &value)| match value {
+                    // Keeps empty columns as wide as the others
+                    0 => "\\phantom{0}".to_string(),
+                    _ if self.is_given(i, j) => {
+                        format!("\\textbf{{{}}}", self.symbols.symbol_for(value))
+                    }
+                    _ => self.symbols.symbol_for(value).to_string(),
+                })
+                .collect::<Vec<_>>();
+            out += &cells.join(" & ");
+            out += " \\\\\n";
+            if i % shape.rows == shape.rows - 1 {
+                out += "\\noalign{\\hrule height 1.2pt}\n";
+            } else {
+                out += "\\hline\n";
+            }
+        }
+        out += "\\end{tabular}}\n";
+        out
+    }
+
+    /// Returns the pencil mark view of the grid: every empty cell is a
+    /// small block listing its [`Sudoku::candidates`], each value in a
+    /// fixed place, `1`-`3` on the first line of a 9x9 board, `4`-`6` on the
+    /// second and `7`-`9` on the third, with `.` for the values ruled out.
+    /// A filled cell shows its value alone in the middle of a blank block.
+    ///
+    /// Lines are drawn between cells of different boxes or regions and
+    /// cells of the same box are set apart by blanks, so this works for a
+    /// Jigsaw Sudoku too.
+    pub fn to_candidate_grid(&self) -> String {
+        let candidates = self.candidates();
+        // The block is as square as the values allow, 3x3 for 9, 2x3 for 6
+        let mut cols = 1;
+        while cols * cols < N {
+            cols += 1;
+        }
+        let rows = N.div_ceil(cols);
+
+        let vertical = |i, j| self.edge_left_of(i, j);
+        let horizontal = |i, j| self.edge_above(i, j);
+        // What cell (i, j) shows at the place of `value`
+        let mark = |i: usize, j: usize, value: u32, middle: bool| match self.grid[i][j] {
+            0 if value as usize > N => ' ',
+            0 if candidates[i][j].contains(&value) => self.symbols.symbol_for(value),
+            0 => '.',
+            filled if middle => self.symbols.symbol_for(filled),
+            _ => ' ',
+        };
+
+        let mut out = String::new();
+        for i in 0..=N {
+            // The line above row i, with a corner where lines cross
+            let mut line = String::new();
+            for j in 0..=N {
+                let across = (j > 0 && horizontal(i, j - 1)) || horizontal(i, j);
+                let down = (i > 0 && vertical(i - 1, j)) || vertical(i, j);
+                line.push(match (across, down) {
+                    (true, true) => '+',
+                    (true, false) => '-',
+                    (false, true) => '|',
+                    (false, false) => ' ',
+                });
+                if j < N {
+                    let edge = if horizontal(i, j) { '-' } else { ' ' };
+                    line.extend(std::iter::repeat_n(edge, cols));
+                }
+            }
+            out += line.trim_end();
+            out.push('\n');
+            if i == N {
+                break;
+            }
+
+            for r in 0..rows {
+                let mut line = String::new();
+                for j in 0..N {
+                    line.push(if vertical(i, j) { '|' } else { ' ' });
+                    for c in 0..cols {
+                        let value = (r * cols + c + 1) as u32;
+                        line.push(mark(i, j, value, r == rows / 2 && c == cols / 2));
+                    }
+                }
+                line.push('|');
+                out += &line;
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// Region of cell `(i, j)`, `None` outside the board, which counts as a
+    /// region of its own.
+    fn region_at(&self, i: usize, j: usize) -> Option<usize> {
+        self.regions.get(i).and_then(|row| row.get(j)).copied()
+    }
+
+    /// Whether a line runs along the left edge of cell `(i, j)`, between
+    /// two boxes or regions or at the border. `j` may be `N` for the right
+    /// border.
+    pub(crate) fn edge_left_of(&self, i: usize, j: usize) -> bool {
+        self.region_at(i, j) != self.region_at(i, j.wrapping_sub(1))
+    }
+
+    /// Whether a line runs along the top edge of cell `(i, j)`, like
+    /// [`Sudoku::edge_left_of`]. `i` may be `N` for the bottom border.
+    pub(crate) fn edge_above(&self, i: usize, j: usize) -> bool {
+        self.region_at(i, j) != self.region_at(i.wrapping_sub(1), j)
+    }
+
+    /// Writes the symbol of cell `(i, j)` for the box drawing, `_` if it's
+    /// empty. If `colored` givens are bold blue and the other values green.
+    pub(crate) fn draw_cell(
+        &self,
+        f: &mut impl std::fmt::Write,
+        i: usize,
+        j: usize,
+        colored: bool,
+    ) -> std::fmt::Result {
+        let symbol = self.symbols.symbol_for(self.grid[i][j]);
+        if self.grid[i][j] == 0 {
+            write!(f, "_")
+        } else if colored {
+            let color = if self.is_given(i, j) { "1;34" } else { "32" };
+            write!(f, "\x1b[{}m{}\x1b[0m", color, symbol)
+        } else {
+            write!(f, "{}", symbol)
+        }
+    }
+
+    /// Boxes whose edges are straight thick lines across the board, the
+    /// whole board for a Jigsaw Sudoku so only the outer border is.
+    fn ruled_shape(&self) -> BoxShape {
+        self.box_shape.unwrap_or(BoxShape { rows: N, cols: N })
+    }
+}
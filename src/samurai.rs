@@ -0,0 +1,237 @@
+use crate::encoding::Encoder;
+use crate::parse::cell_value;
+use crate::{Encoding, ParseGridError, SolveError, Sudoku, Symbols};
+use rsat::{Lit, Solution};
+use std::io::BufRead;
+
+/// Side of the square the five grids of a Samurai Sudoku fit in.
+const SIDE: usize = 21;
+
+/// A Samurai Sudoku: five 9x9 grids arranged in an X, each corner grid
+/// sharing its inner corner box with the middle grid.
+///
+/// Every grid follows the standard rules. The cells of a shared box have a
+/// single set of variables, so both grids see the same values there.
+pub struct Samurai {
+    grids: [[[u32; 9]; 9]; 5],
+    solver: rsat::msat::Solver,
+    /// Set once a clause was already falsified when it was added.
+    contradiction: bool,
+    encoding: Encoding,
+    /// Variables of each cell of the 21x21 square, `None` outside the grids.
+    lits: Vec<Vec<Option<[Lit; 9]>>>,
+}
+
+impl Samurai {
+    /// Top left cell of each grid in the 21x21 square: top left, top right,
+    /// middle, bottom left and bottom right.
+    pub const OFFSETS: [(usize, usize); 5] = [(0, 0), (0, 12), (6, 6), (12, 0), (12, 12)];
+
+    /// Builds the SAT encoding of the five grids, `0` marks an empty cell.
+    ///
+    /// Givens of a shared box may appear in either grid or both, if both
+    /// give a value they have to agree or the puzzle has no solution.
+    pub fn new(grids: [[[u32; 9]; 9]; 5]) -> Self {
+        Self::new_with_encoding(grids, Encoding::default())
+    }
+
+    /// Like [`Samurai::new`], using `encoding` for the "at most one" parts
+    /// of the rules, see [`Sudoku::new_with_encoding`].
+    pub fn new_with_encoding(grids: [[[u32; 9]; 9]; 5], encoding: Encoding) -> Self {
+        let mut samurai = Samurai {
+            grids,
+            solver: rsat::msat::Solver::new(rsat::msat::SolverOptions::default()),
+            contradiction: false,
+            encoding,
+            lits: vec![vec![None; SIDE]; SIDE],
+        };
+        for &(di, dj) in &Self::OFFSETS {
+            for i in di..di + 9 {
+                for j in dj..dj + 9 {
+                    if samurai.lits[i][j].is_none() {
+                        let mut cell = [Lit::new(0, false); 9];
+                        for lit in cell.iter_mut() {
+                            *lit = samurai.new_lit();
+                        }
+                        samurai.lits[i][j] = Some(cell);
+                    }
+                }
+            }
+        }
+
+        for i in 0..SIDE {
+            for j in 0..SIDE {
+                if let Some(cell) = samurai.lits[i][j] {
+                    samurai.add_one_value(&cell);
+                }
+            }
+        }
+
+        // The shared boxes belong to two grids, their rules are added once
+        let mut boxes_done = vec![];
+        for (g, &(di, dj)) in Self::OFFSETS.iter().enumerate() {
+            for l in 0..9 {
+                let top_left = (di + (l / 3) * 3, dj + (l % 3) * 3);
+                let mut units = vec![
+                    (0..9).map(|j| (di + l, dj + j)).collect::<Vec<_>>(),
+                    (0..9).map(|i| (di + i, dj + l)).collect::<Vec<_>>(),
+                ];
+                if !boxes_done.contains(&top_left) {
+                    boxes_done.push(top_left);
+                    units.push(
+                        (0..9)
+                            .map(|c| (top_left.0 + c / 3, top_left.1 + c % 3))
+                            .collect(),
+                    );
+                }
+                for unit in units {
+                    // Each row, column and box contains each value at most once
+                    let cells = unit
+                        .iter()
+                        .map(|&(i, j)| samurai.lits[i][j].expect("unit cells are in a grid"))
+                        .collect::<Vec<_>>();
+                    let cells = cells.iter().map(|cell| &cell[..]).collect::<Vec<_>>();
+                    samurai.add_distinct_values(&cells);
+                }
+            }
+
+            for (i, row) in grids[g].iter().enumerate() {
+                for (j, &value) in row.iter().enumerate() {
+                    if value != 0 {
+                        // Unit clause for already assigned cells
+                        let cell = samurai.lits[di + i][dj + j].expect("grid cells have variables");
+                        samurai.add_clause(vec![cell[value as usize - 1]]);
+                    }
+                }
+            }
+        }
+
+        samurai
+    }
+
+    /// Solves the puzzle, filling in every empty cell of the five grids.
+    ///
+    /// Like [`Sudoku::solve`], fails with [`SolveError::Inconsistent`] if
+    /// the solver returns grids that break the rules, contradict the givens
+    /// or disagree on a shared box.
+    pub fn solve(&mut self) -> Result<(), SolveError> {
+        if self.contradiction {
+            return Err(SolveError::Unsatisfiable);
+        }
+        match self.solver.solve(vec![]) {
+            Solution::Sat(sol) => {
+                let mut grids = [[[0u32; 9]; 9]; 5];
+                for (grid, &(di, dj)) in grids.iter_mut().zip(&Self::OFFSETS) {
+                    for (i, row) in grid.iter_mut().enumerate() {
+                        for (j, value) in row.iter_mut().enumerate() {
+                            let cell =
+                                self.lits[di + i][dj + j].expect("grid cells have variables");
+                            if let Some(k) = cell.iter().position(|lit| sol[lit.var()]) {
+                                *value = k as u32 + 1;
+                            }
+                        }
+                    }
+                }
+                self.grids = self.check_solution(grids)?;
+                Ok(())
+            }
+            Solution::Unsat => Err(SolveError::Unsatisfiable),
+            Solution::Unknown | Solution::Best(_) => Err(SolveError::Unknown),
+        }
+    }
+
+    /// Returns `grids` if each of them is a valid solution keeping the
+    /// givens and every shared cell has the same value in both its grids.
+    fn check_solution(&self, grids: [[[u32; 9]; 9]; 5]) -> Result<[[[u32; 9]; 9]; 5], SolveError> {
+        let mut square = vec![vec![0u32; SIDE]; SIDE];
+        for (g, (grid, &(di, dj))) in grids.iter().zip(&Self::OFFSETS).enumerate() {
+            if !Sudoku::is_valid_solution(grid) {
+                return Err(SolveError::Inconsistent { cell: None });
+            }
+            for (i, row) in grid.iter().enumerate() {
+                for (j, &value) in row.iter().enumerate() {
+                    let given = self.grids[g][i][j];
+                    let shown = &mut square[di + i][dj + j];
+                    if given != 0 && given != value || *shown != 0 && *shown != value {
+                        return Err(SolveError::Inconsistent { cell: None });
+                    }
+                    *shown = value;
+                }
+            }
+        }
+        Ok(grids)
+    }
+
+    /// Returns the current five grids in the order of [`Samurai::OFFSETS`],
+    /// `0` marks an empty cell.
+    pub fn grids(&self) -> &[[[u32; 9]; 9]; 5] {
+        &self.grids
+    }
+}
+
+impl Encoder for Samurai {
+    fn new_lit(&mut self) -> Lit {
+        Lit::new(self.solver.new_var(), false)
+    }
+
+    fn add_clause(&mut self, clause: Vec<Lit>) {
+        if !self.solver.new_clause(clause) {
+            self.contradiction = true;
+        }
+    }
+
+    fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+}
+
+/// Prints the 21x21 square, `.` for empty cells and spaces outside the
+/// grids, the format [`read_samurai`] reads.
+impl std::fmt::Display for Samurai {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut square = vec![vec![' '; SIDE]; SIDE];
+        for (grid, &(di, dj)) in self.grids.iter().zip(&Samurai::OFFSETS) {
+            for (i, row) in grid.iter().enumerate() {
+                for (j, &value) in row.iter().enumerate() {
+                    let shown = &mut square[di + i][dj + j];
+                    // A shared cell may be given in one of its grids only
+                    if *shown == ' ' || value != 0 {
+                        *shown = Symbols::default().symbol_for(value);
+                    }
+                }
+            }
+        }
+        for row in square {
+            writeln!(f, "{}", row.iter().collect::<String>().trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads a Samurai Sudoku as 21 lines of the 21x21 square. Cells outside
+/// the five grids are skipped, so they can be spaces, and lines may end
+/// after their last cell inside a grid.
+pub fn read_samurai<R: BufRead>(reader: R) -> Result<[[[u32; 9]; 9]; 5], ParseGridError> {
+    let mut lines = reader.lines();
+    let mut square = vec![];
+    for _ in 0..SIDE {
+        match lines.next() {
+            Some(line) => square.push(line?.chars().collect::<Vec<char>>()),
+            None => return Err(ParseGridError::TooFewLines { got: square.len() }),
+        }
+    }
+
+    let mut grids = [[[0u32; 9]; 9]; 5];
+    for (grid, &(di, dj)) in grids.iter_mut().zip(&Samurai::OFFSETS) {
+        for (i, row) in grid.iter_mut().enumerate() {
+            let line = &square[di + i];
+            if line.len() < dj + 9 {
+                return Err(ParseGridError::LineTooShort { line: di + i + 1 });
+            }
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = cell_value::<9>(line[dj + j], Symbols::default(), di + i + 1, dj + j + 1)?;
+            }
+        }
+    }
+    Ok(grids)
+}
@@ -0,0 +1,210 @@
+//! `Serialize` and `Deserialize` for [`Sudoku`], enabled by the `serde`
+//! feature.
+//!
+//! The grid and which of its cells are givens are stored as `{"grid":
+//! [[...]], "givens": [[...]]}`, with `N` rows of `N` values and `N` rows of
+//! `N` booleans, along with the rules of the board: `box_shape` as `{"rows":
+//! r, "cols": c}`, or `regions` as `N` rows of `N` region numbers for a
+//! Jigsaw Sudoku, and the variant `constraints` as the fields of
+//! [`crate::Constraints`]. Input without them gets square boxes and no
+//! variant rules.
+//!
+//! The SAT encoding is rebuilt on deserializing with the default
+//! [`crate::Encoding`], and the symbols are reset to the defaults. Cells
+//! set with [`Sudoku::constrain`] aren't stored.
+
+use crate::{BoxShape, Cage, Constraints, Encoding, Sudoku};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+struct Repr {
+    grid: Vec<Vec<u32>>,
+    givens: Vec<Vec<bool>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    box_shape: Option<BoxShape>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    regions: Option<Vec<Vec<usize>>>,
+    #[serde(default)]
+    constraints: Constraints,
+}
+
+impl<const N: usize> Serialize for Sudoku<N> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Repr {
+            grid: self.grid.iter().map(|row| row.to_vec()).collect(),
+            givens: (0..N)
+                .map(|i| (0..N).map(|j| self.is_given(i, j)).collect())
+                .collect(),
+            box_shape: self.box_shape,
+            regions: match self.box_shape {
+                Some(_) => None,
+                None => Some(self.regions.iter().map(|row| row.to_vec()).collect()),
+            },
+            constraints: self.constraints.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, const N: usize> Deserialize<'de> for Sudoku<N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = Repr::deserialize(deserializer)?;
+        if repr.grid.len() != N
+            || repr.givens.len() != N
+            || repr.grid.iter().any(|row| row.len() != N)
+            || repr.givens.iter().any(|row| row.len() != N)
+        {
+            return Err(D::Error::custom(format!(
+                "expected {0} rows of {0} cells",
+                N
+            )));
+        }
+
+        let mut grid = [[0u32; N]; N];
+        let mut givens = [[0u32; N]; N];
+        for i in 0..N {
+            for j in 0..N {
+                let value = repr.grid[i][j];
+                if value as usize > N {
+                    return Err(D::Error::custom(format!(
+                        "value {} at row {}, column {} is out of range 0-{}",
+                        value,
+                        i + 1,
+                        j + 1,
+                        N
+                    )));
+                }
+                if repr.givens[i][j] {
+                    if value == 0 {
+                        return Err(D::Error::custom(format!(
+                            "given at row {}, column {} is empty",
+                            i + 1,
+                            j + 1
+                        )));
+                    }
+                    givens[i][j] = value;
+                }
+                grid[i][j] = value;
+            }
+        }
+
+        let regions = match (repr.box_shape, &repr.regions) {
+            (Some(_), Some(_)) => {
+                return Err(D::Error::custom("expected box_shape or regions, not both"))
+            }
+            (Some(shape), None) => {
+                if shape.rows * shape.cols != N {
+                    return Err(D::Error::custom(format!(
+                        "{}x{} boxes don't tile a board of side {}",
+                        shape.rows, shape.cols, N
+                    )));
+                }
+                None
+            }
+            (None, Some(regions)) => Some(read_regions::<N>(regions).map_err(D::Error::custom)?),
+            (None, None) => {
+                if !(1..=N).any(|b| b * b == N) {
+                    return Err(D::Error::custom(format!(
+                        "a board of side {} needs a box_shape or regions",
+                        N
+                    )));
+                }
+                None
+            }
+        };
+        let constraints = repr.constraints;
+        check_constraints::<N>(&constraints, regions.is_none()).map_err(D::Error::custom)?;
+
+        let mut sudoku = match regions {
+            Some(regions) => {
+                Sudoku::build(givens, &constraints, Encoding::default(), None, regions)
+            }
+            None => {
+                let shape = repr.box_shape.unwrap_or_else(|| BoxShape::square(N));
+                Sudoku::with_options(givens, &constraints, Encoding::default(), shape)
+            }
+        };
+        sudoku.grid = grid;
+        Ok(sudoku)
+    }
+}
+
+/// Checks that `regions` gives every cell a region from `0` to `N - 1` and
+/// every region `N` cells.
+fn read_regions<const N: usize>(regions: &[Vec<usize>]) -> Result<[[usize; N]; N], String> {
+    if regions.len() != N || regions.iter().any(|row| row.len() != N) {
+        return Err(format!("expected {0} rows of {0} regions", N));
+    }
+    let mut out = [[0; N]; N];
+    let mut sizes = [0; N];
+    for (i, row) in regions.iter().enumerate() {
+        for (j, &region) in row.iter().enumerate() {
+            if region >= N {
+                return Err(format!("region {} is out of range 0-{}", region, N - 1));
+            }
+            sizes[region] += 1;
+            out[i][j] = region;
+        }
+    }
+    match sizes.iter().position(|&size| size != N) {
+        Some(region) => Err(format!(
+            "region {} has {} cells instead of {}",
+            region, sizes[region], N
+        )),
+        None => Ok(out),
+    }
+}
+
+/// Checks that every cell the variant rules mention is on the board and
+/// that the rules can be encoded, which the constructors assume.
+fn check_constraints<const N: usize>(constraints: &Constraints, boxed: bool) -> Result<(), String> {
+    let Constraints {
+        cages,
+        inequalities,
+        thermos,
+        evens,
+        odds,
+        row_sandwiches,
+        col_sandwiches,
+        consecutive,
+        ..
+    } = constraints;
+    let cells = cages
+        .iter()
+        .flat_map(|cage| &cage.cells)
+        .chain(
+            inequalities
+                .iter()
+                .flat_map(|inequality| [&inequality.a, &inequality.b]),
+        )
+        .chain(thermos.iter().flat_map(|thermo| &thermo.cells))
+        .chain(evens)
+        .chain(odds)
+        .chain(consecutive.iter().flat_map(|(a, b)| [a, b]));
+    for &(i, j) in cells {
+        if i >= N || j >= N {
+            return Err(format!("cell ({},{}) is outside the board", i, j));
+        }
+    }
+    if let Some(cage) = cages.iter().find(|cage| cage.cells.len() > Cage::MAX_CELLS) {
+        return Err(format!(
+            "cage of {} cells has more than {}",
+            cage.cells.len(),
+            Cage::MAX_CELLS
+        ));
+    }
+    if row_sandwiches.len() > N || col_sandwiches.len() > N {
+        return Err(format!("expected at most {} sandwich clues", N));
+    }
+    // Larger sums can't be reached and would take a huge counter
+    let most = (2..N).sum::<usize>() as u32;
+    let mut sums = row_sandwiches.iter().chain(col_sandwiches).flatten();
+    if let Some(sum) = sums.find(|&&sum| sum > most) {
+        return Err(format!("sandwich sum {} is more than {}", sum, most));
+    }
+    if constraints.windoku && !boxed {
+        return Err("Windoku windows need boxes".to_string());
+    }
+    Ok(())
+}
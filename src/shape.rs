@@ -0,0 +1,29 @@
+/// Shape of the boxes of a board, `rows` cells high and `cols` cells wide.
+///
+/// A board of side `N` needs `rows * cols == N`. The usual boards have
+/// square boxes, see [`BoxShape::square`], while 6x6 boards usually have
+/// boxes of 2 rows by 3 columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoxShape {
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl BoxShape {
+    /// Square boxes for a board of side `n`, which must be a perfect square.
+    pub fn square(n: usize) -> Self {
+        let mut b = 0;
+        while (b + 1) * (b + 1) <= n {
+            b += 1;
+        }
+        assert!(b * b == n, "the board side must be a perfect square");
+        BoxShape { rows: b, cols: b }
+    }
+
+    /// Index of the box holding cell `(i, j)` on a board of side `n`,
+    /// boxes are numbered in reading order.
+    pub(crate) fn box_index(self, n: usize, i: usize, j: usize) -> usize {
+        (i / self.rows) * (n / self.cols) + j / self.cols
+    }
+}
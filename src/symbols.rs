@@ -0,0 +1,58 @@
+/// Characters used for the cell values when reading and printing a grid.
+///
+/// Both sets print `1`-`9` on a 9x9 board, they only differ on larger ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Symbols {
+    /// `1`-`9`, then `A` for 10, `B` for 11 and so on, `0` is an empty cell.
+    #[default]
+    Alphanumeric,
+    /// Hexadecimal digits of the value minus one, `0`-`F` on a 16x16 board,
+    /// so `0` is a value rather than an empty cell.
+    Hex,
+}
+
+impl Symbols {
+    /// Character for a cell value, `.` for an empty cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the set has no character for `value`, that is above
+    /// [`Symbols::max_value`].
+    pub fn symbol_for(self, value: u32) -> char {
+        let digit = match (value, self) {
+            (0, _) => return '.',
+            (value, Symbols::Alphanumeric) => std::char::from_digit(value, 36),
+            (value, Symbols::Hex) => std::char::from_digit(value - 1, 16),
+        };
+        digit
+            .expect("cell value has no symbol")
+            .to_ascii_uppercase()
+    }
+
+    /// Largest value the set has a character for, 35 (`Z`) for
+    /// `Alphanumeric` and 16 (`F`) for `Hex`.
+    pub fn max_value(self) -> u32 {
+        match self {
+            Symbols::Alphanumeric => 35,
+            Symbols::Hex => 16,
+        }
+    }
+
+    /// Value of a cell character on a board of side `n`, `Some(0)` for an
+    /// empty cell and `None` for a character that isn't a value of the
+    /// board. Letters are accepted in either case.
+    pub fn value_for(self, c: char, n: usize) -> Option<u32> {
+        let value = match (c, self) {
+            ('.' | '_', _) => return Some(0),
+            (c, _) if c.is_whitespace() => return Some(0),
+            ('0', Symbols::Alphanumeric) => return Some(0),
+            (c, Symbols::Alphanumeric) => c.to_digit(36)?,
+            (c, Symbols::Hex) => c.to_digit(16)? + 1,
+        };
+        if value as usize <= n {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{BoxShape, Constraints, Encoding, SolveError, Sudoku};
+use std::time::Duration;
+
+/// The rules of a board encoded once, for solving many puzzles in a row.
+///
+/// [`Sudoku::new`] builds a new solver and adds every clause of the rules
+/// for each puzzle. A template keeps a single solver holding only the
+/// rules and passes the givens of each puzzle to it as assumptions, which
+/// are gone again once the call returns. The clauses the solver learns are
+/// implied by the rules alone, so they are kept and help with the next
+/// puzzles.
+pub struct SudokuTemplate<const N: usize = 9> {
+    sudoku: Sudoku<N>,
+}
+
+impl<const N: usize> Default for SudokuTemplate<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SudokuTemplate<N> {
+    /// Encodes the standard rules of a board with square boxes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` isn't a perfect square, and so does
+    /// [`SudokuTemplate::with_constraints`]. Other boards need
+    /// [`SudokuTemplate::with_options`].
+    pub fn new() -> Self {
+        Self::with_options(
+            &Constraints::default(),
+            Encoding::default(),
+            BoxShape::square(N),
+        )
+    }
+
+    /// Encodes the standard rules and those of a variant.
+    pub fn with_constraints(constraints: &Constraints) -> Self {
+        Self::with_options(constraints, Encoding::default(), BoxShape::square(N))
+    }
+
+    /// Encodes the rules like [`Sudoku::with_options`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shape.rows * shape.cols` isn't `N`.
+    pub fn with_options(constraints: &Constraints, encoding: Encoding, shape: BoxShape) -> Self {
+        SudokuTemplate {
+            sudoku: Sudoku::with_options([[0; N]; N], constraints, encoding, shape),
+        }
+    }
+
+    /// Solves the puzzle with the givens of `grid`, `0` marks an empty
+    /// cell. Returns the puzzle, solved, until the next call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the values is above `N`.
+    pub fn solve(&mut self, grid: [[u32; N]; N]) -> Result<&Sudoku<N>, SolveError> {
+        self.set_givens(grid);
+        self.sudoku.solve()?;
+        Ok(&self.sudoku)
+    }
+
+    /// Like [`SudokuTemplate::solve`], giving up after `limit`. See
+    /// [`Sudoku::solve_with_timeout`], the rules are encoded again after a
+    /// timeout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if one of the values is above `N`.
+    pub fn solve_with_timeout(
+        &mut self,
+        grid: [[u32; N]; N],
+        limit: Duration,
+    ) -> Result<&Sudoku<N>, SolveError> {
+        self.set_givens(grid);
+        self.sudoku.solve_with_timeout(limit)?;
+        Ok(&self.sudoku)
+    }
+
+    /// Replaces the givens of the previous puzzle with those of `grid`.
+    fn set_givens(&mut self, grid: [[u32; N]; N]) {
+        for &value in grid.iter().flatten() {
+            assert!(value as usize <= N, "value {} is out of range", value);
+        }
+        self.sudoku.reset_givens(grid);
+    }
+}
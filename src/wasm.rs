@@ -0,0 +1,26 @@
+//! JavaScript bindings, a thin layer over the library for use from the
+//! browser. Build them with `wasm-pack build --target web -- --features wasm`.
+
+use crate::{parse_oneline, Sudoku};
+use wasm_bindgen::prelude::*;
+
+/// Solves a puzzle given as 81 characters and returns the solution in the
+/// same format. Throws an error with a message if the puzzle can't be read
+/// or has no solution.
+#[wasm_bindgen]
+pub fn solve_oneline(input: &str) -> Result<String, JsValue> {
+    let grid = parse_oneline(input).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut sudoku = Sudoku::new(grid);
+    sudoku
+        .solve()
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(sudoku.to_oneline())
+}
+
+/// Counts the solutions of a puzzle given as 81 characters, stopping at
+/// `limit`. Throws an error with a message if the puzzle can't be read.
+#[wasm_bindgen]
+pub fn count_solutions_js(input: &str, limit: u32) -> Result<u32, JsValue> {
+    let grid = parse_oneline(input).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(Sudoku::new(grid).count_solutions(limit as usize) as u32)
+}
@@ -0,0 +1,58 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// A puzzle that solves quickly.
+const SOLVABLE: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+/// Two 5s in the first row.
+const UNSOLVABLE: &str =
+    "535.7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+/// A line with a character that isn't a cell.
+const MALFORMED: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..7X";
+/// A hard puzzle, which takes longer than no time at all.
+const HARD: &str =
+    ".....489......62....47.......8.9......5.4.9...6....5.1....28...3.6......7..9...4.";
+
+/// Runs the binary in `--batch` mode with `args` on one puzzle per line,
+/// returning the exit code.
+fn batch(args: &[&str], puzzles: &[&str]) -> i32 {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_solver"))
+        .arg("--batch")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("binary runs");
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin
+        .write_all((puzzles.join("\n") + "\n").as_bytes())
+        .expect("writing to the binary");
+    drop(stdin);
+    let status = child.wait().expect("binary finishes");
+    status.code().expect("binary exits")
+}
+
+#[test]
+fn solved_batch_succeeds() {
+    assert_eq!(batch(&[], &[SOLVABLE, SOLVABLE]), 0);
+}
+
+#[test]
+fn unsolvable_puzzle_exits_with_1() {
+    assert_eq!(batch(&[], &[SOLVABLE, UNSOLVABLE]), 1);
+}
+
+#[test]
+fn malformed_line_exits_with_3() {
+    assert_eq!(batch(&[], &[MALFORMED, SOLVABLE]), 3);
+    // A parse error is worse than an unsolvable puzzle
+    assert_eq!(batch(&[], &[UNSOLVABLE, MALFORMED, UNSOLVABLE]), 3);
+}
+
+#[test]
+fn timeout_exits_with_4() {
+    assert_eq!(batch(&["--timeout", "0"], &[HARD]), 4);
+    assert_eq!(batch(&["--timeout", "0"], &[UNSOLVABLE, HARD]), 4);
+}
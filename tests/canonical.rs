@@ -0,0 +1,42 @@
+use sudoku_solver::{parse_oneline, Sudoku};
+
+/// The puzzle from the README.
+const PUZZLE: &str =
+    "6....4..1..1....495...1....157....96..4.96..33...45.18....7....76..2......85..3.4";
+
+fn puzzle() -> [[u32; 9]; 9] {
+    parse_oneline(PUZZLE).expect("valid puzzle")
+}
+
+#[test]
+fn rotation_is_equivalent() {
+    let grid = puzzle();
+    let mut rotated = [[0; 9]; 9];
+    for (i, row) in grid.iter().enumerate() {
+        for (j, &value) in row.iter().enumerate() {
+            // A quarter turn clockwise
+            rotated[j][8 - i] = value;
+        }
+    }
+    assert!(Sudoku::new(grid).is_equivalent(&Sudoku::new(rotated)));
+}
+
+#[test]
+fn relabelling_is_equivalent() {
+    let mut relabelled = puzzle();
+    for value in relabelled.iter_mut().flatten() {
+        if *value != 0 {
+            *value = *value % 9 + 1;
+        }
+    }
+    assert!(Sudoku::new(puzzle()).is_equivalent(&Sudoku::new(relabelled)));
+}
+
+#[test]
+fn other_clues_are_not_equivalent() {
+    let mut other = puzzle();
+    // Moves a clue to an empty cell of another row, column and box
+    other[0][0] = 0;
+    other[2][7] = 6;
+    assert!(!Sudoku::new(puzzle()).is_equivalent(&Sudoku::new(other)));
+}
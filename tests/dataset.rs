@@ -0,0 +1,64 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use sudoku_solver::{parse_oneline, Encoding, Sudoku};
+
+/// Hard puzzles, each with its solution, that need search to solve.
+const HARD: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/hard.tsv");
+
+/// Reads a file of `puzzle<TAB>solution` lines, both as 81 characters.
+/// Blank lines are skipped.
+fn load_dataset(path: &str) -> Vec<(String, String)> {
+    std::fs::read_to_string(path)
+        .expect("dataset is readable")
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (puzzle, solution) = line.split_once('\t').expect("line has a tab");
+            (puzzle.to_string(), solution.trim_end().to_string())
+        })
+        .collect()
+}
+
+#[test]
+fn solves_dataset() {
+    let dataset = load_dataset(HARD);
+    assert!(!dataset.is_empty());
+    for (puzzle, solution) in &dataset {
+        for encoding in [Encoding::Pairwise, Encoding::Sequential] {
+            let grid = parse_oneline(puzzle).expect("puzzle parses");
+            let mut sudoku = Sudoku::new_with_encoding(grid, encoding);
+            sudoku.solve().expect("puzzle is solvable");
+            assert!(Sudoku::is_valid_solution(sudoku.grid()));
+            assert_eq!(
+                &sudoku.to_oneline(),
+                solution,
+                "{:?} on {}",
+                encoding,
+                puzzle
+            );
+        }
+    }
+}
+
+#[test]
+fn batch_solves_dataset() {
+    let dataset = load_dataset(HARD);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_solver"))
+        .args(["--batch", "--jobs", "2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary runs");
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    for (puzzle, _) in &dataset {
+        writeln!(stdin, "{}", puzzle).expect("writing to the binary");
+    }
+    drop(stdin);
+    let output = child.wait_with_output().expect("binary finishes");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output is UTF-8");
+    let solutions = stdout.lines().collect::<Vec<_>>();
+    let expected = dataset.iter().map(|(_, s)| s.as_str()).collect::<Vec<_>>();
+    assert_eq!(solutions, expected);
+}
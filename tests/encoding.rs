@@ -0,0 +1,88 @@
+use sudoku_solver::{parse_oneline, Encoding, Sudoku};
+
+#[test]
+fn pairwise_encoding_size() {
+    // 81 * 36 pairs of values per cell, the same per row and column, and
+    // 81 * 18 pairs per box sharing neither, plus a clause per cell
+    let sudoku = Sudoku::new([[0; 9]; 9]);
+    assert_eq!(sudoku.encoding_size(), (729, 3 * 81 * 36 + 81 * 18 + 81));
+}
+
+#[test]
+fn sequential_encoding_size() {
+    // 324 groups of nine literals, one per cell and one per unit and value,
+    // each with 8 auxiliary variables and 23 clauses
+    let sudoku = Sudoku::new_with_encoding([[0; 9]; 9], Encoding::Sequential);
+    assert_eq!(sudoku.encoding_size(), (729 + 324 * 8, 324 * 23 + 81));
+}
+
+#[test]
+fn givens_add_unit_clauses() {
+    let mut grid = [[0; 9]; 9];
+    grid[0][0] = 5;
+    grid[4][7] = 3;
+    let (vars, clauses) = Sudoku::new([[0; 9]; 9]).encoding_size();
+    assert_eq!(Sudoku::new(grid).encoding_size(), (vars, clauses + 2));
+}
+
+#[test]
+fn legend_decodes_a_model_of_the_dimacs() {
+    let puzzle =
+        "...........9..65..2..8...4.1..7.2..3..54......2....9.543.......9...1.6.....26...7";
+    let mut sudoku = Sudoku::new(parse_oneline(puzzle).expect("puzzle parses"));
+    let (dimacs, legend) = sudoku.dimacs_with_legend();
+    sudoku.solve().expect("puzzle has a solution");
+    let solution = *sudoku.grid();
+
+    // The pairwise encoding only has cell variables, the solution sets
+    // exactly those its cells hold
+    assert_eq!(legend.len(), 729);
+    let mut model = vec![false; 730];
+    for &(var, (i, j, value)) in &legend {
+        model[var] = solution[i][j] == value;
+    }
+    let satisfied = |model: &[bool]| {
+        dimacs.lines().skip(1).all(|clause| {
+            clause
+                .split_whitespace()
+                .map(|lit| lit.parse::<i64>().expect("literal is a number"))
+                .take_while(|&lit| lit != 0)
+                .any(|lit| model[lit.unsigned_abs() as usize] == (lit > 0))
+        })
+    };
+    assert!(satisfied(&model));
+
+    // Any other value in a cell breaks a clause
+    let (var, _) = legend[0];
+    model[var] = !model[var];
+    assert!(!satisfied(&model));
+}
+
+#[test]
+fn each_pair_is_encoded_once() {
+    // Emitting both (!a, !b) and (!b, !a) for every pair used to double
+    // the binary clauses to 2 * (3 * 81 * 36 + 81 * 18)
+    let dimacs = Sudoku::new([[0; 9]; 9]).to_dimacs();
+    let mut clauses = dimacs
+        .lines()
+        .skip(1)
+        .map(|clause| {
+            let mut lits = clause.split_whitespace().collect::<Vec<_>>();
+            lits.sort_unstable();
+            lits
+        })
+        .collect::<Vec<_>>();
+    let total = clauses.len();
+    assert_eq!(total, 3 * 81 * 36 + 81 * 18 + 81);
+    clauses.sort_unstable();
+    clauses.dedup();
+    assert_eq!(clauses.len(), total);
+}
+
+#[test]
+#[should_panic(expected = "value 10 is out of range")]
+fn given_above_the_side_panics() {
+    let mut grid = [[0; 9]; 9];
+    grid[4][4] = 10;
+    Sudoku::new(grid);
+}
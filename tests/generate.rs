@@ -0,0 +1,16 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sudoku_solver::Sudoku;
+
+#[test]
+fn same_seed_same_puzzle() {
+    let generate = || {
+        let sudoku: Sudoku = Sudoku::generate(&mut StdRng::seed_from_u64(42));
+        sudoku.to_string()
+    };
+    let first = generate();
+    assert_eq!(first.as_bytes(), generate().as_bytes());
+
+    let other: Sudoku = Sudoku::generate(&mut StdRng::seed_from_u64(43));
+    assert_ne!(first, other.to_string());
+}
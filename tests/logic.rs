@@ -0,0 +1,135 @@
+use sudoku_solver::{parse_oneline, Difficulty, LogicalResult, Sudoku, Technique};
+
+/// Puzzles singles get stuck on but naked pairs finish, with their solutions.
+const NAKED_PAIRS: [(&str, &str); 2] = [
+    (
+        ".7.142..8......1.....6...97..4...7........4.5..6....2..8.37.21.32..84......2....4",
+        "973142658568793142142658397214569783739821465856437921485376219321984576697215834",
+    ),
+    (
+        "...215..9..2.....6.....9..........853...6.....67.4.1...9..314....5....7...34....1",
+        "648215739972384516531679824124793685359168247867542193796831452415926378283457961",
+    ),
+];
+
+/// Puzzles that also need hidden pairs, with their solutions.
+const HIDDEN_PAIRS: [(&str, &str); 3] = [
+    (
+        "...........9..65..2..8...4.1..7.2..3..54......2....9.543.......9...1.6.....26...7",
+        "647125839819346572253879146198752463365491728724638915436987251972513684581264397",
+    ),
+    (
+        ".691....8..3..86....5..9......6..8..45.3....1...52......4...........2.9.83....24.",
+        "269135478143278659785469312317694825452387961698521734924713586576842193831956247",
+    ),
+    (
+        "8..1..5......56...19.....3.....2.....2.6.7..4...8..6...1..9...25..38...9......31.",
+        "864132597237956148195478236486523971329617854751849623613795482542381769978264315",
+    ),
+];
+
+fn sudoku(puzzle: &str) -> Sudoku {
+    Sudoku::new(parse_oneline(puzzle).expect("puzzle parses"))
+}
+
+#[test]
+fn pairs_solve_what_singles_cant() {
+    for (puzzle, solution) in NAKED_PAIRS.iter().chain(&HIDDEN_PAIRS) {
+        let mut sudoku = sudoku(puzzle);
+        assert!(!sudoku.requires_guessing(), "{}", puzzle);
+        assert_eq!(sudoku.solve_logical(), LogicalResult::Solved, "{}", puzzle);
+        assert_eq!(&sudoku.to_oneline(), solution);
+    }
+}
+
+#[test]
+fn hard_puzzle_requires_guessing() {
+    let hard = "7...32...8.....1......4.....6..1..8.1.....6.55.8....4...29.5.........8.4..3..47..";
+    let mut sudoku = sudoku(hard);
+    assert!(sudoku.requires_guessing());
+    assert!(matches!(sudoku.solve_logical(), LogicalResult::Stuck(_)));
+}
+
+#[test]
+fn pairs_rate_medium() {
+    for (puzzle, _) in NAKED_PAIRS.iter().chain(&HIDDEN_PAIRS) {
+        assert_eq!(
+            sudoku(puzzle).difficulty().expect("puzzle is solvable"),
+            Difficulty::Medium,
+            "{}",
+            puzzle
+        );
+    }
+}
+
+/// Checks the steps explaining `puzzle` against its solution and returns
+/// the techniques they used.
+fn explained_techniques(puzzle: &str, solution: &str) -> Vec<Technique> {
+    let mut sudoku = sudoku(puzzle);
+    let steps = sudoku.explain_steps();
+    assert_eq!(&sudoku.to_oneline(), solution, "{}", puzzle);
+    let solution = parse_oneline(solution).expect("solution parses");
+    let empty = puzzle.chars().filter(|&c| c == '.').count();
+    let mut filled = 0;
+    for step in &steps {
+        match step.technique {
+            Technique::NakedSingle | Technique::HiddenSingle => {
+                let (i, j) = step.cells[0];
+                assert_eq!(step.values, vec![solution[i][j]], "{}", step);
+                filled += 1;
+            }
+            Technique::NakedPair | Technique::HiddenPair => {
+                for &((i, j), value) in &step.eliminated {
+                    assert_ne!(solution[i][j], value, "{}", step);
+                }
+            }
+        }
+    }
+    assert_eq!(filled, empty, "{}", puzzle);
+    steps.iter().map(|step| step.technique).collect()
+}
+
+#[test]
+fn steps_explain_the_solution() {
+    for (puzzle, solution) in &NAKED_PAIRS {
+        let techniques = explained_techniques(puzzle, solution);
+        assert!(techniques.contains(&Technique::NakedPair), "{}", puzzle);
+    }
+    for (puzzle, solution) in &HIDDEN_PAIRS {
+        let techniques = explained_techniques(puzzle, solution);
+        assert!(techniques.contains(&Technique::HiddenPair), "{}", puzzle);
+    }
+}
+
+#[test]
+fn candidate_grid_places_each_candidate() {
+    let (puzzle, solution) = NAKED_PAIRS[0];
+    let sudoku = sudoku(puzzle);
+    let solution = parse_oneline(solution).expect("solution parses");
+    let candidates = sudoku.candidates();
+    let drawing = sudoku.to_candidate_grid();
+    let lines = drawing.lines().map(|l| l.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(lines.len(), 9 * 4 + 1);
+    for i in 0..9 {
+        for j in 0..9 {
+            // Each cell is a 3x3 block after a line or blank above and left
+            let block = |value: u32| {
+                let (r, c) = ((value as usize - 1) / 3, (value as usize - 1) % 3);
+                lines[4 * i + 1 + r][4 * j + 1 + c] as char
+            };
+            let given = sudoku.givens()[i][j];
+            if given != 0 {
+                assert_eq!(block(5), std::char::from_digit(given, 10).unwrap());
+                continue;
+            }
+            let shown = (1..=9)
+                .filter(|&value| block(value) != '.')
+                .collect::<Vec<_>>();
+            assert_eq!(shown, candidates[i][j], "R{}C{}", i + 1, j + 1);
+            for &value in &shown {
+                assert_eq!(block(value), std::char::from_digit(value, 10).unwrap());
+            }
+            assert!(shown.contains(&solution[i][j]), "R{}C{}", i + 1, j + 1);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use sudoku_solver::{
+    format_compact, format_json, format_oneline, parse_json, parse_oneline, parse_oneline_with,
+    read_grid, read_grid_with, ParseGridError, Sudoku, Symbols,
+};
+
+/// The README puzzle as nine lines, with `row` replaced.
+fn with_row(row: usize, line: &str) -> String {
+    let mut lines = vec![
+        "53..7....",
+        "6..195...",
+        ".98....6.",
+        "8...6...3",
+        "4..8.3..1",
+        "7...2...6",
+        ".6....28.",
+        "...419..5",
+        "....8..79",
+    ];
+    lines[row] = line;
+    lines.join("\n") + "\n"
+}
+
+#[test]
+fn short_line_with_multibyte_chars() {
+    // At least nine bytes, but only eight characters
+    let input = with_row(2, ".98é....");
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::LineTooShort { line: 3 })
+    ));
+    let input = with_row(4, "4🙂8.3..1");
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::LineTooShort { line: 5 })
+    ));
+}
+
+#[test]
+fn multibyte_chars_are_reported_by_column() {
+    let input = with_row(0, "53..7...🙂");
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::InvalidChar {
+            line: 1,
+            col: 9,
+            ch: '🙂'
+        })
+    ));
+    // A full-width digit is not a digit of the board
+    let input = with_row(8, "....８..79");
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::InvalidChar {
+            line: 9,
+            col: 5,
+            ch: '８'
+        })
+    ));
+}
+
+#[test]
+fn oneline_counts_characters() {
+    let puzzle =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..7é";
+    assert!(matches!(
+        parse_oneline(puzzle),
+        Err(ParseGridError::InvalidChar {
+            line: 1,
+            col: 81,
+            ch: 'é'
+        })
+    ));
+    assert!(matches!(
+        parse_oneline(&puzzle[..puzzle.len() - 2]),
+        Err(ParseGridError::WrongLength {
+            expected: 81,
+            len: 80
+        })
+    ));
+}
+
+#[test]
+fn errors_report_input_lines() {
+    // Two blank lines and a comment before the grid
+    let input = format!("\n\n# a puzzle\n{}", with_row(2, ".98..x.6."));
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::InvalidChar {
+            line: 6,
+            col: 6,
+            ch: 'x'
+        })
+    ));
+    let input = with_row(1, "# the second row is next\n6..19");
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::LineTooShort { line: 3 })
+    ));
+    // Columns count the whitespace dropped around the cells
+    let input = with_row(3, "  8...6..x3  # row 4");
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::InvalidChar {
+            line: 4,
+            col: 10,
+            ch: 'x'
+        })
+    ));
+
+    // The first row of a box drawing is on its second line
+    let mut drawn = Sudoku::new(parse_oneline(README_ONELINE).expect("puzzle parses")).to_string();
+    drawn = drawn.replacen('5', "x", 1);
+    assert!(matches!(
+        read_grid(format!("\n{}", drawn).as_bytes()),
+        Err(ParseGridError::InvalidChar {
+            line: 3,
+            col: 3,
+            ch: 'x'
+        })
+    ));
+    let oneline = format!("\n{}", README_ONELINE.replacen('.', "x", 1));
+    assert!(matches!(
+        read_grid(oneline.as_bytes()),
+        Err(ParseGridError::InvalidChar {
+            line: 2,
+            col: 3,
+            ch: 'x'
+        })
+    ));
+}
+
+#[test]
+fn extra_characters_after_a_row() {
+    let input = with_row(0, "53..7....9X");
+    assert!(matches!(
+        read_grid(input.as_bytes()),
+        Err(ParseGridError::InvalidChar {
+            line: 1,
+            col: 10,
+            ch: '9'
+        })
+    ));
+    // Whitespace and a comment after the cells are fine
+    let input = with_row(0, "53..7....   # row 1");
+    let grid = read_grid(input.as_bytes()).expect("grid parses");
+    assert_eq!(grid[0], [5, 3, 0, 0, 7, 0, 0, 0, 0]);
+    // A row of a box drawing with a cell too many
+    let drawn = "+---\n| 5 3 . | . 7 . | . . . | 1\n";
+    assert!(matches!(
+        read_grid_with::<9, _>(drawn.as_bytes(), Symbols::default()),
+        Err(ParseGridError::InvalidChar {
+            line: 2,
+            col: 27,
+            ch: '1'
+        })
+    ));
+}
+
+#[test]
+fn empty_input() {
+    assert!(matches!(
+        read_grid(&b""[..]),
+        Err(ParseGridError::TooFewLines { got: 0 })
+    ));
+    assert!(matches!(
+        read_grid(&b"\n  \n# nothing yet\n"[..]),
+        Err(ParseGridError::TooFewLines { got: 0 })
+    ));
+}
+
+#[test]
+fn input_ending_early() {
+    let input = with_row(0, "53..7....");
+    let first_rows = input.lines().take(4).collect::<Vec<_>>().join("\n");
+    assert!(matches!(
+        read_grid(first_rows.as_bytes()),
+        Err(ParseGridError::TooFewLines { got: 4 })
+    ));
+}
+
+const README_ONELINE: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+
+#[test]
+fn from_str_reads_both_formats() {
+    let lines = with_row(0, "53..7....")
+        .parse::<Sudoku>()
+        .expect("nine lines parse");
+    let oneline = README_ONELINE.parse::<Sudoku>().expect("one line parses");
+    assert_eq!(lines.grid(), oneline.grid());
+    assert!(matches!(
+        "53..7".parse::<Sudoku>(),
+        Err(ParseGridError::LineTooShort { line: 1 })
+    ));
+}
+
+#[test]
+fn display_parses_back() {
+    let mut sudoku = README_ONELINE.parse::<Sudoku>().expect("puzzle parses");
+    let again = sudoku
+        .to_string()
+        .parse::<Sudoku>()
+        .expect("drawing parses");
+    assert_eq!(again.grid(), sudoku.grid());
+    assert_eq!(again.to_string(), sudoku.to_string());
+
+    sudoku.solve().expect("puzzle is solvable");
+    let solved = sudoku
+        .to_string()
+        .parse::<Sudoku>()
+        .expect("drawing parses");
+    assert_eq!(solved.grid(), sudoku.grid());
+}
+
+#[test]
+fn oneline_round_trip() {
+    let grid = parse_oneline(README_ONELINE).expect("puzzle parses");
+    assert_eq!(Sudoku::new(grid).to_oneline(), README_ONELINE);
+
+    // Other empty cell markers come back as dots
+    let zeros = README_ONELINE.replace('.', "0");
+    let grid = parse_oneline(&zeros).expect("puzzle parses");
+    assert_eq!(Sudoku::new(grid).to_oneline(), README_ONELINE);
+}
+
+#[test]
+fn grids_format_without_a_sudoku() {
+    let grid = parse_oneline(README_ONELINE).expect("puzzle parses");
+    assert_eq!(format_oneline(&grid, Symbols::default()), README_ONELINE);
+    assert_eq!(parse_json(&format_json(&grid)).expect("JSON parses"), grid);
+    let compact = format_compact(&grid, Symbols::default());
+    assert_eq!(read_grid(compact.as_bytes()).expect("grid parses"), grid);
+
+    // A 6x6 board has no square boxes, which `Sudoku::new` would need
+    let six = "...2....5.4..3....5...1.1..63.6..5..";
+    let grid = parse_oneline_with::<6>(six, Symbols::default()).expect("puzzle parses");
+    assert_eq!(format_oneline(&grid, Symbols::default()), six);
+    let compact = format_compact(&grid, Symbols::default());
+    let read = read_grid_with::<6, _>(compact.as_bytes(), Symbols::default());
+    assert_eq!(read.expect("grid parses"), grid);
+}
+
+#[test]
+fn round_trip_16x16_with_both_symbol_sets() {
+    // A valid full grid with every fifth cell emptied
+    let mut grid = [[0u32; 16]; 16];
+    for (i, row) in grid.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            if (16 * i + j) % 5 != 0 {
+                *value = ((4 * (i % 4) + i / 4 + j) % 16) as u32 + 1;
+            }
+        }
+    }
+    for symbols in [Symbols::Alphanumeric, Symbols::Hex] {
+        let mut sudoku = Sudoku::<16>::new(grid);
+        sudoku.set_symbols(symbols);
+
+        let oneline = sudoku.to_oneline();
+        assert_eq!(oneline.chars().count(), 256);
+        // 16 is G in letters after 9 and F in hex, where 0 is 1
+        let sixteen = if symbols == Symbols::Hex { 'F' } else { 'G' };
+        assert!(oneline.contains(sixteen));
+        assert_eq!(oneline.contains('0'), symbols == Symbols::Hex);
+        assert_eq!(parse_oneline_with::<16>(&oneline, symbols).ok(), Some(grid));
+        let compact = sudoku.to_compact();
+        assert_eq!(
+            read_grid_with::<16, _>(compact.as_bytes(), symbols).ok(),
+            Some(grid)
+        );
+        let drawing = sudoku.to_string();
+        assert_eq!(
+            read_grid_with::<16, _>(drawing.as_bytes(), symbols).ok(),
+            Some(grid)
+        );
+    }
+}
+
+#[test]
+fn values_without_a_symbol() {
+    let mut grid = [[0u32; 25]; 25];
+    grid[0][0] = 17;
+    assert_eq!(
+        format_oneline(&grid, Symbols::Alphanumeric).chars().next(),
+        Some('H')
+    );
+    let hex = std::panic::catch_unwind(|| format_oneline(&grid, Symbols::Hex));
+    assert!(hex.is_err());
+}
+
+#[test]
+#[should_panic(expected = "Hex can't show the values of a 25x25 board")]
+fn set_symbols_checks_the_side() {
+    Sudoku::<25>::new([[0; 25]; 25]).set_symbols(Symbols::Hex);
+}
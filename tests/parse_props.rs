@@ -0,0 +1,85 @@
+use proptest::prelude::*;
+use sudoku_solver::{parse_json, parse_oneline, read_grid, ParseGridError};
+
+/// Characters the parsers give a meaning to, plus some that take more than
+/// one byte in UTF-8, so random strings of them get past the length checks
+/// more often than arbitrary text would.
+const ALPHABET: &[char] = &[
+    '0', '1', '5', '9', 'A', 'z', '.', '_', ' ', '\t', '\n', '#', '|', '+', '-', '[', ']', ',',
+    'é', '€', '５', '🙂',
+];
+
+/// Strategy for strings made of [`ALPHABET`].
+fn grid_like(max_len: usize) -> impl Strategy<Value = String> {
+    prop::collection::vec(prop::sample::select(ALPHABET), 0..max_len)
+        .prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Strategy for the README puzzle with a few characters replaced, in the
+/// one-line and the nine-line format.
+fn mutated_puzzle() -> impl Strategy<Value = String> {
+    let puzzle =
+        "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+    (
+        any::<bool>(),
+        prop::collection::vec((0..81usize, prop::sample::select(ALPHABET)), 0..4),
+    )
+        .prop_map(move |(lines, changes)| {
+            let mut chars = puzzle.chars().collect::<Vec<_>>();
+            for (c, ch) in changes {
+                chars[c] = ch;
+            }
+            if lines {
+                chars
+                    .chunks(9)
+                    .map(|row| row.iter().collect::<String>() + "\n")
+                    .collect()
+            } else {
+                chars.into_iter().collect()
+            }
+        })
+}
+
+/// A parsed grid only holds values of the board.
+fn assert_in_range(result: Result<[[u32; 9]; 9], ParseGridError>) -> Result<(), TestCaseError> {
+    if let Ok(grid) = result {
+        prop_assert!(grid.iter().flatten().all(|&value| value <= 9));
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn parse_oneline_never_panics(s in grid_like(100)) {
+        assert_in_range(parse_oneline(&s))?;
+    }
+
+    #[test]
+    fn parse_json_never_panics(s in grid_like(100)) {
+        assert_in_range(parse_json(&s))?;
+    }
+
+    #[test]
+    fn read_grid_never_panics(s in grid_like(200)) {
+        assert_in_range(read_grid(s.as_bytes()))?;
+    }
+
+    #[test]
+    fn arbitrary_text_never_panics(s in any::<String>()) {
+        assert_in_range(parse_oneline(&s))?;
+        assert_in_range(parse_json(&s))?;
+        assert_in_range(read_grid(s.as_bytes()))?;
+    }
+
+    #[test]
+    fn arbitrary_bytes_never_panic(bytes in prop::collection::vec(any::<u8>(), 0..200)) {
+        // Invalid UTF-8 is an `IoError` once the reader gets to it
+        assert_in_range(read_grid(&bytes[..]))?;
+    }
+
+    #[test]
+    fn mutated_puzzles_never_panic(s in mutated_puzzle()) {
+        assert_in_range(parse_oneline(&s))?;
+        assert_in_range(read_grid(s.as_bytes()))?;
+    }
+}
@@ -0,0 +1,67 @@
+use sudoku_solver::{read_samurai, Encoding, Samurai, Sudoku};
+
+/// A Samurai Sudoku, as the 21 lines of its square.
+const SAMURAI: [&str; 21] = [
+    "1..456...   .......89",
+    "45..8.2..   4..7.9.13",
+    ".....3456   78.1.34.6",
+    ".14....9.   21.36.8.7",
+    "3.894.5..   365......",
+    ".....2..4   ....1.3..",
+    "...6.8.7.4...3.....78",
+    "....941.52.7....7.6.2",
+    "...5.13..519......5.1",
+    "      .14....59",
+    "      ..9.728.4",
+    "      6.794.123",
+    ".3.7.94.6.....51..6.9",
+    ".8.3.6..18234...5.1.8",
+    "....258.......7689...",
+    "31.4.76..   ....6....",
+    "...2.8.37   5..79....",
+    ".9.61..45   .7....4..",
+    "6..53.97.   .3....982",
+    "8.49..362   .5..137.4",
+    "9.3....14   .6.872..3",
+];
+
+/// Cell `(i, j)` of the 21x21 square in each grid covering it.
+fn square_cell(grids: &[[[u32; 9]; 9]; 5], i: usize, j: usize) -> Vec<u32> {
+    grids
+        .iter()
+        .zip(&Samurai::OFFSETS)
+        .filter(|(_, &(di, dj))| (di..di + 9).contains(&i) && (dj..dj + 9).contains(&j))
+        .map(|(grid, &(di, dj))| grid[i - di][j - dj])
+        .collect()
+}
+
+#[test]
+fn shared_boxes_agree() {
+    let grids = read_samurai(SAMURAI.join("\n").as_bytes()).expect("valid puzzle");
+    for encoding in [Encoding::Pairwise, Encoding::Sequential] {
+        let mut samurai = Samurai::new_with_encoding(grids, encoding);
+        samurai.solve().expect("solvable");
+        let solved = samurai.grids();
+        for (grid, puzzle) in solved.iter().zip(&grids) {
+            assert!(Sudoku::is_valid_solution(grid));
+            for (&value, &given) in grid.iter().flatten().zip(puzzle.iter().flatten()) {
+                assert!(given == 0 || given == value);
+            }
+        }
+        // The middle grid shares a corner box with each of the others
+        let (di, dj) = Samurai::OFFSETS[2];
+        let mut shared = 0;
+        for i in di..di + 9 {
+            for j in dj..dj + 9 {
+                let values = square_cell(solved, i, j);
+                assert!(values.iter().all(|&value| value == values[0]));
+                if values.len() == 2 {
+                    shared += 1;
+                }
+            }
+        }
+        assert_eq!(shared, 4 * 9);
+        assert_eq!(solved[0][6][6], solved[2][0][0]);
+        assert_eq!(solved[4][2][2], solved[2][8][8]);
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "serde")]
+
+use sudoku_solver::{parse_oneline, parse_oneline_with, BoxShape, Sudoku, Symbols};
+
+fn round_trip<const N: usize>(sudoku: &Sudoku<N>) -> Sudoku<N> {
+    let json = serde_json::to_string(sudoku).expect("serializes");
+    serde_json::from_str(&json).expect("deserializes")
+}
+
+#[test]
+fn round_trip_keeps_the_rules() {
+    let six = "...2....5.4..3....5...1.1..63.6..5..";
+    let six = parse_oneline_with::<6>(six, Symbols::default()).expect("valid puzzle");
+    let shape = BoxShape { rows: 2, cols: 3 };
+    let mut read = round_trip(&Sudoku::with_box_shape(six, shape));
+    assert_eq!(read.box_shape(), Some(shape));
+    assert!(read.has_unique_solution());
+
+    let regions = [[0, 0, 0, 1], [0, 2, 1, 1], [2, 2, 3, 1], [2, 3, 3, 3]];
+    let read = round_trip(&Sudoku::<4>::with_regions([[0; 4]; 4], regions));
+    assert_eq!(read.box_shape(), None);
+    assert_eq!(read.regions(), &regions);
+
+    // Has several solutions without the diagonals
+    let x = "...4........7.........23.5.21...5......9...45....1.3..89......4.......2..........";
+    let x = parse_oneline(x).expect("valid puzzle");
+    assert!(!Sudoku::new(x).has_unique_solution());
+    let mut read = round_trip(&Sudoku::with_diagonals(x));
+    assert!(read.has_unique_solution());
+}
+
+#[test]
+fn bad_rules_are_errors() {
+    let grid = vec![vec![0; 6]; 6];
+    let givens = vec![vec![false; 6]; 6];
+    let plain = serde_json::json!({ "grid": grid, "givens": givens });
+    let error = serde_json::from_value::<Sudoku<6>>(plain)
+        .err()
+        .expect("no boxes");
+    assert!(error.to_string().contains("needs a box_shape or regions"));
+
+    let grid = vec![vec![0; 9]; 9];
+    let givens = vec![vec![false; 9]; 9];
+    let evens = serde_json::json!({ "evens": [[0, 9]] });
+    let off_board = serde_json::json!({ "grid": grid, "givens": givens, "constraints": evens });
+    let error = serde_json::from_value::<Sudoku>(off_board)
+        .err()
+        .expect("cell off the board");
+    assert!(error.to_string().contains("outside the board"));
+}
@@ -0,0 +1,33 @@
+use sudoku_solver::{parse_oneline_with, BoxShape, Sudoku, Symbols};
+
+/// A 6x6 puzzle with boxes of 2 rows by 3 columns.
+const SIX: &str = "...2....5.4..3....5...1.1..63.6..5..";
+const SIX_SOLUTION: &str = "416253325146231465564312152634643521";
+const SIX_BOXES: BoxShape = BoxShape { rows: 2, cols: 3 };
+
+#[test]
+fn six_by_six_with_2x3_boxes() {
+    let puzzle = parse_oneline_with::<6>(SIX, Symbols::default()).expect("valid puzzle");
+    assert!(Sudoku::validate_givens_with_box_shape(&puzzle, SIX_BOXES).is_ok());
+    assert!(Sudoku::with_box_shape(puzzle, SIX_BOXES).has_unique_solution());
+
+    let mut sudoku = Sudoku::with_box_shape(puzzle, SIX_BOXES);
+    sudoku.solve().expect("solvable");
+    assert_eq!(sudoku.to_oneline(), SIX_SOLUTION);
+    assert!(Sudoku::is_valid_solution_with_box_shape(
+        sudoku.grid(),
+        SIX_BOXES
+    ));
+    // 3x2 boxes cut across the 2x3 ones.
+    let sideways = BoxShape { rows: 3, cols: 2 };
+    assert!(!Sudoku::is_valid_solution_with_box_shape(
+        sudoku.grid(),
+        sideways
+    ));
+}
+
+#[test]
+#[should_panic(expected = "perfect square")]
+fn six_by_six_needs_a_box_shape() {
+    Sudoku::<6>::is_valid_solution(&[[0; 6]; 6]);
+}
@@ -0,0 +1,156 @@
+use proptest::prelude::*;
+use sudoku_solver::{Encoding, SolveError, Sudoku};
+
+/// Strategy for a permutation of `0..n`.
+fn permutation(n: usize) -> impl Strategy<Value = Vec<usize>> {
+    Just((0..n).collect::<Vec<_>>()).prop_shuffle()
+}
+
+/// Strategy for valid full grids: a fixed solution with its values
+/// relabelled, its bands, stacks, rows within a band and columns within a
+/// stack reordered, and possibly transposed. All of these keep it valid.
+fn full_grid() -> impl Strategy<Value = [[u32; 9]; 9]> {
+    (
+        permutation(9),
+        permutation(3),
+        prop::collection::vec(permutation(3), 3),
+        permutation(3),
+        prop::collection::vec(permutation(3), 3),
+        any::<bool>(),
+    )
+        .prop_map(|(values, bands, rows, stacks, cols, transpose)| {
+            let mut grid = [[0u32; 9]; 9];
+            for (i, grid_i) in grid.iter_mut().enumerate() {
+                for (j, grid_ij) in grid_i.iter_mut().enumerate() {
+                    let r = 3 * bands[i / 3] + rows[i / 3][i % 3];
+                    let c = 3 * stacks[j / 3] + cols[j / 3][j % 3];
+                    let (r, c) = if transpose { (c, r) } else { (r, c) };
+                    *grid_ij = values[(3 * r + r / 3 + c) % 9] as u32 + 1;
+                }
+            }
+            grid
+        })
+}
+
+/// Strategy for a puzzle made from a full grid by blanking random cells,
+/// anywhere from none to all of them.
+fn puzzle() -> impl Strategy<Value = [[u32; 9]; 9]> {
+    solved_puzzle().prop_map(|(_, puzzle)| puzzle)
+}
+
+/// Strategy for a full grid along with a [`puzzle`] made from it.
+fn solved_puzzle() -> impl Strategy<Value = ([[u32; 9]; 9], [[u32; 9]; 9])> {
+    (
+        full_grid(),
+        prop::collection::vec(any::<bool>(), 81),
+        0..=81usize,
+    )
+        .prop_map(|(grid, blanks, keep)| {
+            let mut puzzle = grid;
+            for (c, &blank) in blanks.iter().enumerate() {
+                // Blank fewer cells for a larger `keep`
+                if blank && c >= keep {
+                    puzzle[c / 9][c % 9] = 0;
+                }
+            }
+            (grid, puzzle)
+        })
+}
+
+fn assert_solves(puzzle: [[u32; 9]; 9], encoding: Encoding) -> Result<(), TestCaseError> {
+    let mut sudoku = Sudoku::new_with_encoding(puzzle, encoding);
+    prop_assert!(sudoku.solve().is_ok());
+    let solution = sudoku.grid();
+    prop_assert!(Sudoku::is_valid_solution(solution));
+    for (puzzle_i, solution_i) in puzzle.iter().zip(solution) {
+        for (&given, &value) in puzzle_i.iter().zip(solution_i) {
+            prop_assert!(given == 0 || given == value);
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn generated_grids_are_valid(grid in full_grid()) {
+        prop_assert!(Sudoku::is_valid_solution(&grid));
+    }
+
+    #[test]
+    fn pairwise_solution_is_valid_and_keeps_givens(puzzle in puzzle()) {
+        assert_solves(puzzle, Encoding::Pairwise)?;
+    }
+
+    #[test]
+    fn sequential_solution_is_valid_and_keeps_givens(puzzle in puzzle()) {
+        assert_solves(puzzle, Encoding::Sequential)?;
+    }
+
+    #[test]
+    fn solution_keeps_constrained_cells(
+        (grid, puzzle) in solved_puzzle(),
+        picks in prop::collection::vec(any::<bool>(), 81),
+    ) {
+        let mut sudoku = Sudoku::new(puzzle);
+        let mut constrained = vec![];
+        for (c, &pick) in picks.iter().enumerate() {
+            let (i, j) = (c / 9, c % 9);
+            if pick && puzzle[i][j] == 0 {
+                sudoku.constrain((i, j), grid[i][j]);
+                constrained.push((i, j));
+            }
+        }
+        prop_assert!(sudoku.solve().is_ok());
+        prop_assert_eq!(sudoku.givens(), &puzzle);
+        for (i, j) in constrained {
+            prop_assert_eq!(sudoku.grid()[i][j], grid[i][j]);
+        }
+    }
+
+    #[test]
+    fn solving_keeps_the_forced_cells(puzzle in puzzle()) {
+        let mut sudoku = Sudoku::new(puzzle);
+        let forced = sudoku.forced_cells();
+        let unique = sudoku.has_unique_solution();
+        prop_assert!(sudoku.solve().is_ok());
+        prop_assert_eq!(sudoku.forced_cells(), forced.clone());
+        for &((i, j), value) in &forced {
+            prop_assert_eq!(puzzle[i][j], 0);
+            prop_assert_eq!(sudoku.grid()[i][j], value);
+        }
+        if unique {
+            let empty = puzzle.iter().flatten().filter(|&&value| value == 0).count();
+            prop_assert_eq!(forced.len(), empty);
+        }
+    }
+
+    #[test]
+    fn wrong_constrained_cell_is_reported(
+        (grid, puzzle) in solved_puzzle(),
+        pick in any::<prop::sample::Index>(),
+        offset in 1..9u32,
+    ) {
+        let empty = (0..81)
+            .map(|c| (c / 9, c % 9))
+            .filter(|&(i, j)| puzzle[i][j] == 0)
+            .collect::<Vec<_>>();
+        prop_assume!(!empty.is_empty());
+        // Filling in every empty cell leaves no room for a wrong value
+        let mut sudoku = Sudoku::new(puzzle);
+        for &(i, j) in &empty {
+            sudoku.constrain((i, j), grid[i][j]);
+        }
+        let (i, j) = empty[pick.index(empty.len())];
+        let wrong = (grid[i][j] + offset - 1) % 9 + 1;
+        sudoku.constrain((i, j), wrong);
+        match sudoku.solve() {
+            Err(SolveError::Inconsistent { cell }) => {
+                prop_assert_eq!(cell, Some(((i, j), wrong)));
+            }
+            other => prop_assert!(false, "unexpected result {:?}", other),
+        }
+
+        sudoku.clear_constraints();
+        prop_assert!(sudoku.solve().is_ok());
+    }
+}
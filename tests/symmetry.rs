@@ -0,0 +1,78 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sudoku_solver::{parse_oneline, Sudoku, Symmetry};
+
+/// Clues symmetric under a half turn and both mirrors, but not a quarter
+/// turn or the diagonals.
+const SYMMETRIC: &str =
+    "003020600900305001001806400008102900700000008006708200002609500800203009005010300";
+
+fn sudoku(puzzle: &str) -> Sudoku {
+    Sudoku::new(parse_oneline(puzzle).expect("puzzle parses"))
+}
+
+#[test]
+fn known_puzzle_symmetries() {
+    assert_eq!(
+        sudoku(SYMMETRIC).clue_symmetry(),
+        vec![
+            Symmetry::Rotational180,
+            Symmetry::Horizontal,
+            Symmetry::Vertical
+        ]
+    );
+}
+
+#[test]
+fn only_clue_positions_count() {
+    // Other values in the same places keep the symmetries
+    let relabelled = SYMMETRIC
+        .replace('3', "x")
+        .replace('9', "3")
+        .replace('x', "9");
+    assert_eq!(
+        sudoku(&relabelled).clue_symmetry(),
+        sudoku(SYMMETRIC).clue_symmetry()
+    );
+
+    // A clue without its mirror images breaks all of them
+    let mut moved = parse_oneline(SYMMETRIC).expect("puzzle parses");
+    moved[0][2] = 0;
+    assert_eq!(Sudoku::new(moved).clue_symmetry(), vec![]);
+}
+
+#[test]
+fn diagonal_mirrors() {
+    // Half turn symmetric clues, mirrored along the diagonal
+    let mut grid = [[0; 9]; 9];
+    for &(i, j) in &[(0, 1), (2, 5), (4, 4), (7, 3)] {
+        for &(i, j) in &[(i, j), (j, i), (8 - i, 8 - j), (8 - j, 8 - i)] {
+            grid[i][j] = 1;
+        }
+    }
+    assert_eq!(
+        Sudoku::new(grid).clue_symmetry(),
+        vec![
+            Symmetry::Rotational180,
+            Symmetry::Diagonal,
+            Symmetry::AntiDiagonal
+        ]
+    );
+}
+
+#[test]
+fn generated_puzzles_have_their_symmetry() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for &symmetry in &[
+        Symmetry::Rotational180,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::Rotational90,
+        Symmetry::Diagonal,
+        Symmetry::AntiDiagonal,
+    ] {
+        let mut sudoku: Sudoku = Sudoku::generate_with_symmetry(&mut rng, symmetry);
+        assert!(sudoku.clue_symmetry().contains(&symmetry), "{:?}", symmetry);
+        assert!(sudoku.has_unique_solution(), "{:?}", symmetry);
+    }
+}
@@ -0,0 +1,54 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// The example from the README as nine lines.
+const PUZZLE: &str = "\
+6....4..1
+..1....49
+5...1....
+157....96
+..4.96..3
+3...45.18
+....7....
+76..2....
+..85..3.4
+";
+
+/// Runs the binary with `--transcode from to` on `input`, returning stdout.
+fn transcode(from: &str, to: &str, input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_solver"))
+        .args(["--transcode", from, to])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("binary runs");
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin
+        .write_all(input.as_bytes())
+        .expect("writing to the binary");
+    drop(stdin);
+    let output = child.wait_with_output().expect("binary finishes");
+    assert!(output.status.success(), "{} to {} failed", from, to);
+    String::from_utf8(output.stdout).expect("output is UTF-8")
+}
+
+#[test]
+fn grid_oneline_grid_round_trip() {
+    let oneline = transcode("grid", "oneline", PUZZLE);
+    assert_eq!(oneline, PUZZLE.replace('\n', "") + "\n");
+    assert_eq!(transcode("oneline", "grid", &oneline), PUZZLE);
+}
+
+#[test]
+fn grid_json_grid_round_trip() {
+    let json = transcode("grid", "json", PUZZLE);
+    assert!(json.starts_with("[[6,0,0,0,0,4,0,0,1],"));
+    assert_eq!(transcode("json", "grid", &json), PUZZLE);
+}
+
+#[test]
+fn oneline_json_oneline_round_trip() {
+    let oneline = PUZZLE.replace('\n', "") + "\n";
+    let json = transcode("oneline", "json", &oneline);
+    assert_eq!(transcode("json", "oneline", &json), oneline);
+}
@@ -0,0 +1,306 @@
+use sudoku_solver::{parse_oneline, Constraints, Inequality, SolveError, Sudoku, Thermo};
+
+/// A Windoku with 17 clues, which has many solutions under the standard
+/// rules alone.
+const WINDOKU: &str =
+    "..........5.7.9....8...3.....42......1...53.4....1.5.....5...7..7..........9.....";
+const WINDOKU_SOLUTION: &str =
+    "123456789456789213789123456534297861217865394698314527941538672872641935365972148";
+
+#[test]
+fn windoku_needs_the_windows() {
+    let puzzle = parse_oneline(WINDOKU).expect("valid puzzle");
+    assert!(Sudoku::with_windoku(puzzle).has_unique_solution());
+    assert!(Sudoku::new(puzzle).count_solutions(2) > 1);
+
+    let mut sudoku = Sudoku::with_windoku(puzzle);
+    sudoku.solve().expect("solvable");
+    assert_eq!(sudoku.to_oneline(), WINDOKU_SOLUTION);
+    let grid = sudoku.grid();
+    for (top, left) in [(1, 1), (1, 5), (5, 1), (5, 5)] {
+        let mut window = (0..9)
+            .map(|c| grid[top + c / 3][left + c % 3])
+            .collect::<Vec<_>>();
+        window.sort_unstable();
+        assert_eq!(window, (1..=9).collect::<Vec<_>>());
+    }
+}
+
+/// A full grid to take the sandwich clues from.
+const SANDWICH_SOLUTION: &str =
+    "269135478143278659785469312317694825452387961698521734924713586576842193831956247";
+
+/// Sandwich clues of the rows, or of the columns if `columns`, of `grid`.
+fn sandwich_sums(grid: &[[u32; 9]; 9], columns: bool) -> [Option<u32>; 9] {
+    std::array::from_fn(|l| {
+        let line = (0..9)
+            .map(|p| if columns { grid[p][l] } else { grid[l][p] })
+            .collect::<Vec<_>>();
+        let one = line.iter().position(|&v| v == 1)?;
+        let nine = line.iter().position(|&v| v == 9)?;
+        Some(line[one.min(nine) + 1..one.max(nine)].iter().sum())
+    })
+}
+
+#[test]
+fn sandwich_sums_alone_solve() {
+    let solution = parse_oneline(SANDWICH_SOLUTION).expect("valid grid");
+    let (rows, cols) = (
+        sandwich_sums(&solution, false),
+        sandwich_sums(&solution, true),
+    );
+    // All 18 clues and no givens, they allow a few grids
+    let mut sudoku = Sudoku::with_sandwiches([[0; 9]; 9], rows, cols);
+    sudoku.solve().expect("solvable");
+    let grid = sudoku.grid();
+    assert!(Sudoku::is_valid_solution(grid));
+    assert_eq!(sandwich_sums(grid, false), rows);
+    assert_eq!(sandwich_sums(grid, true), cols);
+}
+
+#[test]
+fn sandwich_sums_solve_a_puzzle() {
+    let puzzle = parse_oneline(
+        ".691....8..3..86....5..9......6..8..45.3....1...52......4...........2.9.83....24.",
+    )
+    .expect("valid puzzle");
+    let solution = parse_oneline(SANDWICH_SOLUTION).expect("valid grid");
+    let (mut rows, cols) = (
+        sandwich_sums(&solution, false),
+        sandwich_sums(&solution, true),
+    );
+    let mut sudoku = Sudoku::with_sandwiches(puzzle, rows, cols);
+    sudoku.solve().expect("solvable");
+    assert_eq!(sudoku.to_oneline(), SANDWICH_SOLUTION);
+
+    // The first row has 1 and 9 next to each other
+    assert_eq!(rows[0], Some(0));
+    rows[0] = Some(5);
+    let mut sudoku = Sudoku::with_sandwiches(puzzle, rows, cols);
+    assert!(matches!(sudoku.solve(), Err(SolveError::Unsatisfiable)));
+}
+
+/// An X-Sudoku, which has other solutions under the standard rules.
+const X_SUDOKU: &str =
+    "...4........7.........23.5.21...5......9...45....1.3..89......4.......2..........";
+const X_SUDOKU_SOLUTION: &str =
+    "123456789456789213789123456214365897368972145975814362892531674537648921641297538";
+
+#[test]
+fn x_sudoku_solution_differs_from_plain() {
+    let puzzle = parse_oneline(X_SUDOKU).expect("valid puzzle");
+    let mut sudoku = Sudoku::with_diagonals(puzzle);
+    assert!(sudoku.has_unique_solution());
+    sudoku.solve().expect("solvable");
+    assert_eq!(sudoku.to_oneline(), X_SUDOKU_SOLUTION);
+    let grid = sudoku.grid();
+    for mut diagonal in [
+        (0..9).map(|i| grid[i][i]).collect::<Vec<_>>(),
+        (0..9).map(|i| grid[i][8 - i]).collect(),
+    ] {
+        diagonal.sort_unstable();
+        assert_eq!(diagonal, (1..=9).collect::<Vec<_>>());
+    }
+
+    let solution = *grid;
+    let plain = Sudoku::new(puzzle).solve_all(Some(2));
+    assert_eq!(plain.len(), 2);
+    assert!(plain.iter().any(|grid| *grid != solution));
+}
+
+/// An anti-king Sudoku, which has other solutions under the standard rules.
+const ANTI_KING: &str =
+    ".2..5....4....92...8.............8.73.5...1.....2.4..5..16.........3.......5..6.2";
+const ANTI_KING_SOLUTION: &str =
+    "123456789456789213789123456214365897365897124897214365531672948642938571978541632";
+
+/// Returns true if two diagonally adjacent cells of `grid` are equal, the
+/// orthogonal ones are already apart under the standard rules.
+fn king_move_repeats(grid: &[[u32; 9]; 9]) -> bool {
+    (0..8).any(|i| {
+        (0..8).any(|j| grid[i][j] == grid[i + 1][j + 1] || grid[i][j + 1] == grid[i + 1][j])
+    })
+}
+
+#[test]
+fn anti_king_keeps_diagonal_neighbours_apart() {
+    let puzzle = parse_oneline(ANTI_KING).expect("valid puzzle");
+    let constraints = Constraints {
+        anti_king: true,
+        ..Constraints::default()
+    };
+    let mut sudoku = Sudoku::with_constraints(puzzle, &constraints);
+    assert!(sudoku.has_unique_solution());
+    sudoku.solve().expect("solvable");
+    assert_eq!(sudoku.to_oneline(), ANTI_KING_SOLUTION);
+    assert!(!king_move_repeats(sudoku.grid()));
+
+    // Some standard solution of the same givens breaks the rule
+    let plain = Sudoku::new(puzzle).solve_all(Some(10));
+    assert!(plain.len() > 1);
+    assert!(plain.iter().any(king_move_repeats));
+}
+
+/// `a < b` if `less`, `a > b` otherwise.
+fn inequality(a: (usize, usize), b: (usize, usize), less: bool) -> Inequality {
+    Inequality {
+        a,
+        b,
+        a_less_than_b: less,
+    }
+}
+
+#[test]
+fn futoshiki_5x5() {
+    // Two givens and seven inequalities, all in the bottom rows
+    let mut puzzle = [[0; 5]; 5];
+    puzzle[0][0] = 3;
+    puzzle[2][3] = 4;
+    let inequalities = vec![
+        inequality((2, 4), (3, 4), false),
+        inequality((3, 1), (3, 2), true),
+        inequality((3, 1), (4, 1), false),
+        inequality((3, 3), (3, 4), true),
+        inequality((3, 3), (4, 3), false),
+        inequality((3, 4), (4, 4), true),
+        inequality((4, 1), (4, 2), true),
+    ];
+    let mut sudoku = Sudoku::<5>::futoshiki(puzzle, inequalities.clone());
+    assert!(sudoku.has_unique_solution());
+    sudoku.solve().expect("solvable");
+    assert_eq!(
+        sudoku.grid(),
+        &[
+            [3, 1, 4, 5, 2],
+            [4, 5, 2, 3, 1],
+            [2, 3, 1, 4, 5],
+            [1, 4, 5, 2, 3],
+            [5, 2, 3, 1, 4],
+        ]
+    );
+    let grid = sudoku.grid();
+    for inequality in &inequalities {
+        let (a, b) = (
+            grid[inequality.a.0][inequality.a.1],
+            grid[inequality.b.0][inequality.b.1],
+        );
+        assert_eq!(a < b, inequality.a_less_than_b, "{:?}", inequality);
+    }
+
+    // The givens alone leave many Latin squares
+    assert!(Sudoku::<5>::futoshiki(puzzle, vec![]).count_solutions(2) > 1);
+}
+
+/// A Thermo-Sudoku with a thermometer along the empty first row, made from
+/// the solution of [`X_SUDOKU`], whose first row is 1 to 9.
+const THERMO: &str =
+    "...........6.8.....8....4...1....8..3..97......5..4.62..2..1......64........9...8";
+
+#[test]
+fn full_row_thermo_forces_the_solution() {
+    let puzzle = parse_oneline(THERMO).expect("valid puzzle");
+    // Nine strictly increasing values can only be 1 to 9 in order
+    let thermo = Thermo {
+        cells: (0..9).map(|j| (0, j)).collect(),
+    };
+    let mut sudoku = Sudoku::with_thermos(puzzle, vec![thermo.clone()]);
+    assert!(sudoku.has_unique_solution());
+    sudoku.solve().expect("solvable");
+    assert_eq!(sudoku.to_oneline(), X_SUDOKU_SOLUTION);
+    assert_eq!(sudoku.grid()[0], [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+
+    let mut sudoku = Sudoku::with_thermos(puzzle, vec![thermo]);
+    assert!(matches!(
+        sudoku.solve_assuming(&[((0, 4), 6)]),
+        Err(SolveError::Unsatisfiable)
+    ));
+    assert!(Sudoku::new(puzzle).count_solutions(2) > 1);
+}
+
+#[test]
+fn parity_marks_hold_and_clash_with_givens() {
+    let puzzle = parse_oneline(X_SUDOKU).expect("valid puzzle");
+    let solution = parse_oneline(X_SUDOKU_SOLUTION).expect("valid grid");
+    // Marking every cell with the parity of the X-Sudoku solution
+    let (evens, odds) = (0..81)
+        .map(|c| (c / 9, c % 9))
+        .partition::<Vec<_>, _>(|&(i, j)| solution[i][j].is_multiple_of(2));
+    let mut sudoku = Sudoku::with_parity(puzzle, evens.clone(), odds.clone());
+    sudoku.solve().expect("solvable");
+    let grid = sudoku.grid();
+    assert!(evens.iter().all(|&(i, j)| grid[i][j].is_multiple_of(2)));
+    assert!(odds.iter().all(|&(i, j)| !grid[i][j].is_multiple_of(2)));
+
+    // The given 7 in R2C4 can't be even
+    assert_eq!(puzzle[1][3], 7);
+    let mut sudoku = Sudoku::with_parity(puzzle, vec![(1, 3)], vec![]);
+    assert!(matches!(sudoku.solve(), Err(SolveError::Unsatisfiable)));
+}
+
+/// Every pair of orthogonally adjacent cells of a 9x9 board.
+fn adjacent_pairs() -> Vec<((usize, usize), (usize, usize))> {
+    let mut pairs = vec![];
+    for i in 0..9 {
+        for j in 0..9 {
+            if j + 1 < 9 {
+                pairs.push(((i, j), (i, j + 1)));
+            }
+            if i + 1 < 9 {
+                pairs.push(((i, j), (i + 1, j)));
+            }
+        }
+    }
+    pairs
+}
+
+#[test]
+fn consecutive_marks_hold() {
+    let solution = parse_oneline(X_SUDOKU_SOLUTION).expect("valid grid");
+    let consecutive =
+        |grid: &[[u32; 9]; 9], ((ia, ja), (ib, jb)): ((usize, usize), (usize, usize))| {
+            grid[ia][ja].abs_diff(grid[ib][jb]) == 1
+        };
+    let (marks, others) = adjacent_pairs()
+        .into_iter()
+        .partition::<Vec<_>, _>(|&pair| consecutive(&solution, pair));
+
+    let mut sudoku = Sudoku::with_consecutive([[0; 9]; 9], marks.clone(), false);
+    sudoku.solve().expect("solvable");
+    let grid = *sudoku.grid();
+    assert!(marks.iter().all(|&pair| consecutive(&grid, pair)));
+
+    let mut sudoku = Sudoku::with_consecutive([[0; 9]; 9], marks.clone(), true);
+    sudoku.solve().expect("solvable");
+    let grid = *sudoku.grid();
+    assert!(marks.iter().all(|&pair| consecutive(&grid, pair)));
+    assert!(!others.iter().any(|&pair| consecutive(&grid, pair)));
+
+    // A mark between a 4 and a 6
+    let mut puzzle = [[0; 9]; 9];
+    puzzle[0][0] = 4;
+    puzzle[0][1] = 6;
+    let mut sudoku = Sudoku::with_consecutive(puzzle, vec![((0, 0), (0, 1))], false);
+    assert!(matches!(sudoku.solve(), Err(SolveError::Unsatisfiable)));
+}
+
+#[test]
+fn unmarked_pairs_are_nonconsecutive() {
+    let mut puzzle = [[0; 9]; 9];
+    puzzle[0][0] = 4;
+    puzzle[0][1] = 5;
+    assert!(Sudoku::with_consecutive(puzzle, vec![], false)
+        .solve()
+        .is_ok());
+    let mut sudoku = Sudoku::with_consecutive(puzzle, vec![], true);
+    assert!(matches!(sudoku.solve(), Err(SolveError::Unsatisfiable)));
+
+    // Marking the pair lifts the negative rule for it alone
+    let mut sudoku = Sudoku::with_consecutive(puzzle, vec![((0, 0), (0, 1))], true);
+    sudoku.solve().expect("solvable");
+    let grid = sudoku.grid();
+    for ((ia, ja), (ib, jb)) in adjacent_pairs() {
+        if ((ia, ja), (ib, jb)) != ((0, 0), (0, 1)) {
+            assert_ne!(grid[ia][ja].abs_diff(grid[ib][jb]), 1);
+        }
+    }
+}